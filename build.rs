@@ -0,0 +1,26 @@
+fn main() {
+    #[cfg(feature = "capi")]
+    generate_header();
+}
+
+#[cfg(feature = "capi")]
+fn generate_header() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR is not set");
+    // same place for every profile and target, `CARGO_TARGET_DIR` is honored
+    let target_dir =
+        std::env::var("CARGO_TARGET_DIR").unwrap_or_else(|_| format!("{}/target", crate_dir));
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+    println!("cargo:rerun-if-env-changed=CARGO_TARGET_DIR");
+
+    // Only ffi.rs is parsed, every exported type lives there
+    cbindgen::Builder::new()
+        .with_config(
+            cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+                .expect("Failed reading cbindgen.toml"),
+        )
+        .with_src(format!("{}/src/ffi.rs", crate_dir))
+        .generate()
+        .expect("Failed generating C header")
+        .write_to_file(format!("{}/include/noface.h", target_dir));
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+
+use crate::{
+    error::Error,
+    image::Image,
+    model::{data::VectorizedTensor, Embedding, FaceSelection, Model},
+    result::Result,
+    setting::Setting,
+};
+
+pub use batch::BatchArgs;
+pub use detect::DetectArgs;
+pub use embed::EmbedArgs;
+pub use serve::ServeArgs;
+pub use swap::SwapArgs;
+
+pub mod batch;
+pub mod detect;
+pub mod embed;
+pub mod serve;
+pub mod swap;
+
+pub(crate) const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+pub(crate) const VIDEO_EXTENSIONS: [&str; 5] = ["mp4", "mov", "avi", "mkv", "webm"];
+
+/// Face swap, launches gui when no command is given
+#[derive(Parser, Debug)]
+#[command(name = "noface", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Swap source face into target image (or animated gif/apng/webp)
+    Swap(SwapArgs),
+    /// Swap source face into every image and video of a directory tree
+    Batch(BatchArgs),
+    /// Export detected faces as json without swapping
+    Detect(DetectArgs),
+    /// Store identity embeddings of face images for reuse as swap source
+    Embed(EmbedArgs),
+    /// Serve swap and detect over a local http api
+    Serve(ServeArgs),
+}
+
+impl Command {
+    pub fn run(self, setting: Setting) -> Result<()> {
+        match self {
+            Command::Swap(args) => args.run(&setting),
+            Command::Batch(args) => args.run(&setting),
+            Command::Detect(args) => args.run(&setting),
+            Command::Embed(args) => args.run(&setting),
+            Command::Serve(args) => args.run(&setting),
+        }
+    }
+}
+
+/// Swap source from face image or `.emb` embedding file, `selection` picks
+/// the face of images with several
+pub(crate) fn load_source(
+    model: &mut Model,
+    path: &Path,
+    selection: &FaceSelection,
+) -> Result<VectorizedTensor> {
+    if Embedding::is_embedding_file(path) {
+        return model.source_from_embedding(&Embedding::load(path)?);
+    }
+    let (_, src) = model.vectorize_tensor_with(
+        Image::from_path(path.to_path_buf(), None)?.into(),
+        selection,
+    )?;
+    Ok(src)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MediaKind {
+    Image,
+    Video,
+}
+
+pub(crate) fn media_kind(path: &Path) -> Option<MediaKind> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    if IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        return Some(MediaKind::Image);
+    }
+    VIDEO_EXTENSIONS
+        .contains(&ext.as_str())
+        .then_some(MediaKind::Video)
+}
+
+/// Image and video files under dir (recursive, sorted)
+pub(crate) fn walk_media(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir).map_err(Error::as_unknown_error)? {
+            let path = entry.map_err(Error::as_unknown_error)?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if media_kind(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,415 @@
+use std::{
+    collections::VecDeque,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cv::{VideoReader, VideoWriter},
+    image::{Animation, ImageSequence},
+    model::{data::VectorizedTensor, FaceSelection, Model, Tensor},
+    setting::Setting,
+    Error, Result,
+};
+
+use super::{load_source, media_kind, walk_media, MediaKind};
+
+#[derive(clap::Args, Debug)]
+pub struct BatchArgs {
+    /// Image containing source face or `.emb` embedding file
+    #[arg(short, long)]
+    pub source: PathBuf,
+    /// Face of a multi-face source: best, largest, all, an index or indices
+    /// to average (0,2)
+    #[arg(long, default_value = "best")]
+    pub source_face: FaceSelection,
+    /// Directory processed recursively, or a frame pattern
+    /// (shots/shot_%05d.png)
+    #[arg(short, long)]
+    pub input: PathBuf,
+    /// Output directory mirroring input structure, outside the input
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// Parallel jobs, each job loads its own models
+    #[arg(short, long, default_value_t = 1)]
+    pub jobs: usize,
+    /// Reprocess files already existing in output
+    #[arg(long)]
+    pub overwrite: bool,
+    /// Write json summary to file instead of stdout
+    #[arg(long)]
+    pub summary: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+struct Job {
+    input: PathBuf,
+    output: PathBuf,
+    kind: MediaKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+enum JobStatus {
+    Done,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct JobReport {
+    input: PathBuf,
+    output: PathBuf,
+    status: JobStatus,
+    frames: usize,
+    faces: usize,
+    seconds: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct Summary {
+    total: usize,
+    done: usize,
+    skipped: usize,
+    failed: usize,
+    faces: usize,
+    seconds: f64,
+    files: Vec<JobReport>,
+}
+
+struct Progress {
+    total: usize,
+    finished: AtomicUsize,
+    faces: AtomicUsize,
+    started_at: Instant,
+}
+
+impl Progress {
+    fn report(&self, report: &JobReport) {
+        let finished = self.finished.fetch_add(1, Ordering::SeqCst) + 1;
+        let faces = self.faces.fetch_add(report.faces, Ordering::SeqCst) + report.faces;
+        let eta = self
+            .started_at
+            .elapsed()
+            .mul_f64((self.total - finished) as f64 / finished as f64);
+
+        eprintln!(
+            "[{}/{}] {:?} {} | faces: {} | eta: {}",
+            finished,
+            self.total,
+            report.status,
+            report.input.display(),
+            faces,
+            format_duration(eta)
+        );
+        if let Some(err) = &report.error {
+            eprintln!("  error: {}", err);
+        }
+    }
+}
+
+impl BatchArgs {
+    #[tracing::instrument(name = "Running batch command", skip(setting), err)]
+    pub fn run(self, setting: &Setting) -> Result<()> {
+        let jobs = collect_jobs(&self.input, &self.output)?;
+        let worker_count = self.jobs.clamp(1, jobs.len().max(1));
+
+        // Source gets embedded once and shared across all jobs
+        let mut first_model = Model::new(&setting.config.model)?;
+        let src = load_source(&mut first_model, &self.source, &self.source_face)?;
+
+        let mut models = vec![first_model];
+        for _ in 1..worker_count {
+            models.push(Model::new(&setting.config.model)?);
+        }
+
+        let progress = Progress {
+            total: jobs.len(),
+            finished: AtomicUsize::new(0),
+            faces: AtomicUsize::new(0),
+            started_at: Instant::now(),
+        };
+        let queue = Mutex::new(jobs.into_iter().collect::<VecDeque<Job>>());
+        let reports = Mutex::new(Vec::<JobReport>::with_capacity(progress.total));
+
+        std::thread::scope(|scope| {
+            for mut model in models {
+                let (queue, reports, progress, src) = (&queue, &reports, &progress, &src);
+                let overwrite = self.overwrite;
+                scope.spawn(move || loop {
+                    let Some(job) = queue.lock().ok().and_then(|mut q| q.pop_front()) else {
+                        break;
+                    };
+                    let report = run_job(&mut model, job, src, overwrite);
+                    progress.report(&report);
+                    if let Ok(mut reports) = reports.lock() {
+                        reports.push(report);
+                    }
+                });
+            }
+        });
+
+        let mut files = reports.into_inner().map_err(Error::as_guard_error)?;
+        files.sort_by(|a, b| a.input.cmp(&b.input));
+        let count = |status: JobStatus| files.iter().filter(|r| r.status == status).count();
+        let summary = Summary {
+            total: progress.total,
+            done: count(JobStatus::Done),
+            skipped: count(JobStatus::Skipped),
+            failed: count(JobStatus::Failed),
+            faces: files.iter().map(|r| r.faces).sum(),
+            seconds: progress.started_at.elapsed().as_secs_f64(),
+            files,
+        };
+
+        let summary_json =
+            serde_json::to_string_pretty(&summary).map_err(Error::as_unknown_error)?;
+        match &self.summary {
+            Some(path) => std::fs::write(path, summary_json).map_err(Error::as_unknown_error)?,
+            None => println!("{}", summary_json),
+        }
+
+        if summary.failed > 0 {
+            return Err(Error::UnknownError(
+                format!("{} of {} files failed", summary.failed, summary.total).into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn run_job(model: &mut Model, job: Job, src: &VectorizedTensor, overwrite: bool) -> JobReport {
+    let started_at = Instant::now();
+    let mut report = JobReport {
+        input: job.input.clone(),
+        output: job.output.clone(),
+        status: JobStatus::Done,
+        frames: 0,
+        faces: 0,
+        seconds: 0.,
+        error: None,
+    };
+
+    if !overwrite && job.output.exists() {
+        report.status = JobStatus::Skipped;
+        return report;
+    }
+
+    // written next to the output and renamed once complete, so an interrupted
+    // job isn't mistaken for a finished one
+    let partial = partial_path(&job.output);
+    let result = job
+        .output
+        .parent()
+        .map_or(Ok(()), |dir| {
+            std::fs::create_dir_all(dir).map_err(Error::as_unknown_error)
+        })
+        .and_then(|_| match job.kind {
+            MediaKind::Image => process_image(model, &job.input, &partial, src),
+            MediaKind::Video => process_video(model, &job.input, &partial, src),
+        })
+        .and_then(|counts| {
+            std::fs::rename(&partial, &job.output).map_err(Error::as_unknown_error)?;
+            Ok(counts)
+        });
+
+    match result {
+        Ok((frames, faces)) => {
+            report.frames = frames;
+            report.faces = faces;
+        }
+        Err(err) => {
+            let _ = std::fs::remove_file(&partial);
+            report.status = JobStatus::Failed;
+            report.error = Some(err.to_string());
+        }
+    }
+    report.seconds = started_at.elapsed().as_secs_f64();
+    report
+}
+
+/// Output file name while its job runs, keeps the extension for the encoder
+fn partial_path(output: &Path) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let ext = output
+        .extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy()))
+        .unwrap_or_default();
+    output.with_file_name(format!(".{}.partial{}", stem, ext))
+}
+
+/// (frames, faces)
+fn process_image(
+    model: &mut Model,
+    input: &Path,
+    output: &Path,
+    src: &VectorizedTensor,
+) -> Result<(usize, usize)> {
+    let mut faces = 0;
+    if Animation::is_animated(input)? {
+        Animation::check_output(output)?;
+        let animation = Animation::from_path(input)?.try_map(|img| {
+            let (swapped, found) = swap_frame(model, img.into(), src)?;
+            faces += found;
+            Ok(swapped.into())
+        })?;
+        animation.save(output)?;
+        return Ok((animation.frames.len(), faces));
+    }
+
+    // 16 bit frames are written back as 16 bit, upconverted from the 8 bit result
+    let frame = ImageSequence::read_frame(input)?;
+    let (swapped, found) = swap_frame(model, frame.image.clone().into(), src)?;
+    ImageSequence::write_frame_to(&frame, &swapped.into(), output)?;
+    Ok((1, found))
+}
+
+/// (frames, faces)
+fn process_video(
+    model: &mut Model,
+    input: &Path,
+    output: &Path,
+    src: &VectorizedTensor,
+) -> Result<(usize, usize)> {
+    let mut reader = VideoReader::open(input)?;
+    let mut writer = VideoWriter::create(output, reader.fps, reader.size)?;
+
+    let (mut frames, mut faces) = (0, 0);
+    while let Some(mat) = reader.next_frame()? {
+        let (swapped, found) = swap_frame(model, mat.into(), src)?;
+        writer.write_image(&swapped.into())?;
+        frames += 1;
+        faces += found;
+    }
+    Ok((frames, faces))
+}
+
+fn swap_frame(
+    model: &mut Model,
+    mut tar: Tensor,
+    src: &VectorizedTensor,
+) -> Result<(Tensor, usize)> {
+    let faces = model.detect(tar.clone())?;
+    if let Some(face) = faces.first() {
+        model.swap_face(&mut tar, face, src.clone())?;
+    }
+    Ok((tar, faces.len()))
+}
+
+fn collect_jobs(input: &Path, output: &Path) -> Result<Vec<Job>> {
+    if !input.is_dir() && ImageSequence::is_sequence(input) {
+        let sequence = ImageSequence::from_pattern(input.to_path_buf())?;
+        sequence.ensure_output_dir(output)?;
+        return Ok(sequence
+            .paths()
+            .iter()
+            .filter_map(|path| {
+                Some(Job {
+                    input: path.clone(),
+                    output: output.join(path.file_name()?),
+                    kind: MediaKind::Image,
+                })
+            })
+            .collect());
+    }
+
+    check_output_dir(input, output)?;
+    Ok(walk_media(input)?
+        .into_iter()
+        .filter_map(|path| {
+            let kind = media_kind(&path)?;
+            let mut job_output = output.join(path.strip_prefix(input).ok()?);
+            // apng | webp animations can only be written back as gif
+            if kind == MediaKind::Image && Animation::is_animated(&path).unwrap_or(false) {
+                job_output.set_extension("gif");
+            }
+            Some(Job {
+                input: path,
+                output: job_output,
+                kind,
+            })
+        })
+        .collect())
+}
+
+/// Output inside the input tree would skip (or with `--overwrite` replace)
+/// the originals and get walked as input on the next run
+fn check_output_dir(input: &Path, output: &Path) -> Result<()> {
+    let input = input.canonicalize().map_err(Error::as_unknown_error)?;
+    let output = resolve_path(output)?;
+    if output.starts_with(&input) {
+        return Err(Error::UnknownError(
+            format!(
+                "Output directory {} must be outside the input directory {}",
+                output.display(),
+                input.display()
+            )
+            .into(),
+        ));
+    }
+    Ok(())
+}
+
+/// Absolute `path` through its closest existing ancestor, it may not exist yet
+fn resolve_path(path: &Path) -> Result<PathBuf> {
+    let path = std::path::absolute(path).map_err(Error::as_unknown_error)?;
+    let mut missing = vec![];
+    let mut existing = path.as_path();
+    loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            return Ok(missing
+                .into_iter()
+                .rev()
+                .fold(resolved, |path, part| path.join(part)));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return Ok(path.clone()),
+        }
+    }
+}
+
+// hh:mm:ss
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::collect_jobs;
+
+    #[test]
+    fn rejects_output_inside_input() {
+        let dir = std::env::temp_dir().join(format!("noface_batch_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let input = dir.join("input");
+        std::fs::create_dir_all(input.join("nested")).unwrap();
+        std::fs::write(input.join("nested").join("photo.jpg"), b"").unwrap();
+
+        assert!(collect_jobs(&input, &input).is_err());
+        assert!(collect_jobs(&input, &input.join("out")).is_err());
+        assert!(collect_jobs(&input, &input.join("nested").join("..")).is_err());
+
+        let jobs = collect_jobs(&input, &dir.join("output")).unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(
+            jobs[0].output,
+            dir.join("output").join("nested").join("photo.jpg")
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,215 @@
+use std::{
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    cv::{VideoReader, VideoWriter},
+    image::Image,
+    model::{
+        data::{Face, Tracker},
+        Model, Tensor,
+    },
+    setting::Setting,
+    Error, Result,
+};
+
+use super::{media_kind, walk_media, MediaKind};
+
+#[derive(clap::ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectFormat {
+    /// Single json array
+    Json,
+    /// One json object per line
+    Jsonl,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DetectArgs {
+    /// Images, videos or directories
+    #[arg(required = true)]
+    pub inputs: Vec<PathBuf>,
+    /// Output file, stdout when not given
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    #[arg(short, long, value_enum, default_value_t = DetectFormat::Json)]
+    pub format: DetectFormat,
+    /// Assign track ids to faces across video frames
+    #[arg(long)]
+    pub track: bool,
+    /// Directory to write images and videos annotated with boxes and keypoints
+    #[arg(long)]
+    pub annotate: Option<PathBuf>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct FaceRecord {
+    file: PathBuf,
+    /// Video frame index
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frame: Option<usize>,
+    #[serde(flatten)]
+    face: Face,
+}
+
+impl DetectArgs {
+    #[tracing::instrument(name = "Running detect command", skip(setting), err)]
+    pub fn run(self, setting: &Setting) -> Result<()> {
+        // (input root, file), annotated files mirror their path under the root
+        let mut files = vec![];
+        for input in &self.inputs {
+            if input.is_dir() {
+                files.extend(
+                    walk_media(input)?
+                        .into_iter()
+                        .map(|file| (input.clone(), file)),
+                );
+            } else {
+                let root = input.parent().unwrap_or(Path::new("")).to_path_buf();
+                files.push((root, input.clone()));
+            }
+        }
+        if let Some(dir) = &self.annotate {
+            check_annotate_dir(dir, files.iter().map(|(root, _)| root.as_path()))?;
+        }
+
+        let mut model = Model::new(&setting.config.model)?;
+        let mut out: Box<dyn Write> = match &self.output {
+            Some(path) => Box::new(BufWriter::new(
+                std::fs::File::create(path).map_err(Error::as_unknown_error)?,
+            )),
+            None => Box::new(std::io::stdout().lock()),
+        };
+
+        let mut all_records = vec![];
+        for (root, file) in files {
+            let records = match media_kind(&file) {
+                Some(MediaKind::Video) => self.detect_video(&mut model, &root, &file)?,
+                _ => self.detect_image(&mut model, &root, &file)?,
+            };
+
+            match self.format {
+                DetectFormat::Json => all_records.extend(records),
+                DetectFormat::Jsonl => {
+                    for record in records {
+                        serde_json::to_writer(&mut out, &record)
+                            .map_err(Error::as_unknown_error)?;
+                        writeln!(out).map_err(Error::as_unknown_error)?;
+                    }
+                }
+            }
+        }
+
+        if self.format == DetectFormat::Json {
+            serde_json::to_writer_pretty(&mut out, &all_records)
+                .map_err(Error::as_unknown_error)?;
+            writeln!(out).map_err(Error::as_unknown_error)?;
+        }
+        out.flush().map_err(Error::as_unknown_error)
+    }
+
+    fn detect_image(&self, model: &mut Model, root: &Path, file: &Path) -> Result<Vec<FaceRecord>> {
+        let mut tensor = Tensor::from(Image::from_path(file.to_path_buf(), None)?);
+        let faces = model.detect(tensor.clone())?;
+
+        if let Some(dir) = &self.annotate {
+            annotate(&mut tensor, &faces)?;
+            let out_path = annotated_path(dir, root, file)?;
+            Image::from(tensor)
+                .save(out_path)
+                .map_err(Error::ImageError)?;
+        }
+
+        Ok(faces
+            .into_iter()
+            .map(|face| FaceRecord {
+                file: file.to_path_buf(),
+                frame: None,
+                face,
+            })
+            .collect())
+    }
+
+    fn detect_video(&self, model: &mut Model, root: &Path, file: &Path) -> Result<Vec<FaceRecord>> {
+        let mut reader = VideoReader::open(file)?;
+        let mut writer = match &self.annotate {
+            Some(dir) => Some(VideoWriter::create(
+                &annotated_path(dir, root, file)?,
+                reader.fps,
+                reader.size,
+            )?),
+            None => None,
+        };
+        let mut tracker = self.track.then(Tracker::default);
+
+        let (mut records, mut frame) = (vec![], 0);
+        while let Some(mat) = reader.next_frame()? {
+            let mut tensor = Tensor::from(mat);
+            let mut faces = model.detect(tensor.clone())?;
+            if let Some(tracker) = tracker.as_mut() {
+                tracker.update(&mut faces);
+            }
+
+            if let Some(writer) = writer.as_mut() {
+                annotate(&mut tensor, &faces)?;
+                writer.write_image(&tensor.into())?;
+            }
+
+            records.extend(faces.into_iter().map(|face| FaceRecord {
+                file: file.to_path_buf(),
+                frame: Some(frame),
+                face,
+            }));
+            frame += 1;
+        }
+        Ok(records)
+    }
+}
+
+fn annotate(tensor: &mut Tensor, faces: &[Face]) -> Result<()> {
+    for face in faces {
+        let (x1, y1, x2, y2) = face.bbox;
+        tensor.border((x1 as usize, y1 as usize, x2 as usize, y2 as usize))?;
+        for [x, y] in face.keypoints.iter() {
+            tensor.mark((*x as usize, *y as usize), 2)?;
+        }
+    }
+    Ok(())
+}
+
+/// Annotating into an input root would overwrite the inputs
+fn check_annotate_dir<'a>(dir: &Path, roots: impl Iterator<Item = &'a Path>) -> Result<()> {
+    let Ok(dir) = dir.canonicalize() else {
+        // not created yet, can't be an input
+        return Ok(());
+    };
+    for root in roots {
+        // parent of a bare file name
+        let root = if root.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            root
+        };
+        if root.canonicalize().is_ok_and(|root| root == dir) {
+            return Err(Error::UnknownError(
+                format!("Annotate directory {} is an input directory", dir.display()).into(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// `file` relative to its input `root`, under `dir`
+fn annotated_path(dir: &Path, root: &Path, file: &Path) -> Result<PathBuf> {
+    let relative = match file.strip_prefix(root) {
+        Ok(relative) if relative.file_name().is_some() => relative,
+        _ => Path::new(file.file_name().ok_or_else(|| {
+            Error::UnknownError(format!("Invalid file path: {}", file.display()).into())
+        })?),
+    };
+    let out_path = dir.join(relative);
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent).map_err(Error::as_unknown_error)?;
+    }
+    Ok(out_path)
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use crate::{
+    image::Image,
+    model::{Embedding, EmbeddingNorm, EmbeddingSource, FaceSelection, Model, VECTORIZATION_DIM},
+    setting::Setting,
+    Result,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct EmbedArgs {
+    /// Images of the same identity
+    #[arg(required = true)]
+    pub images: Vec<PathBuf>,
+    /// Faces used from each image: best, largest, all, an index or indices
+    /// (0,2), each selected face is stored as its own vector
+    #[arg(long, default_value = "best")]
+    pub face: FaceSelection,
+    /// Output embedding file (.emb)
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// Also export vectors as NumPy .npy
+    #[arg(long)]
+    pub npy: Option<PathBuf>,
+    /// Store L2 normalized vectors instead of raw model output
+    #[arg(long)]
+    pub normalize: bool,
+}
+
+impl EmbedArgs {
+    #[tracing::instrument(name = "Running embed command", skip(setting), err)]
+    pub fn run(self, setting: &Setting) -> Result<()> {
+        let mut model = Model::new(&setting.config.model)?;
+        let mut embedding = Embedding::new(
+            model.recognizer_name(),
+            VECTORIZATION_DIM,
+            if self.normalize {
+                EmbeddingNorm::L2
+            } else {
+                EmbeddingNorm::None
+            },
+        );
+
+        for image in &self.images {
+            let faces =
+                model.embed_selected(Image::from_path(image.clone(), None)?.into(), &self.face)?;
+            let source = EmbeddingSource::from_path(image)?;
+            for face in faces.iter() {
+                embedding.push(face.raw.clone(), source.clone())?;
+            }
+            eprintln!("embedded {} ({} faces)", image.display(), faces.len());
+        }
+
+        embedding.save(&self.output)?;
+        if let Some(npy) = &self.npy {
+            embedding.save_npy(npy)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
+
+use crate::{
+    server::{serve, ServerConfig},
+    setting::Setting,
+    Result,
+};
+
+#[derive(clap::Args, Debug)]
+pub struct ServeArgs {
+    /// Listen address, keep it on loopback unless behind a trusted proxy
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub host: IpAddr,
+    #[arg(short, long, default_value_t = 7860)]
+    pub port: u16,
+    /// Max request body size in MiB
+    #[arg(long, default_value_t = 32)]
+    pub max_body_mb: usize,
+    /// Max jobs running or waiting, requests over it get 503
+    #[arg(long, default_value_t = 8)]
+    pub queue: usize,
+    /// Folder of `.emb` files usable as swap source by id
+    #[arg(long, default_value = "data/embeddings")]
+    pub embeddings: PathBuf,
+    /// Default source face image, `.emb` file or stored embedding id
+    #[arg(short, long)]
+    pub source: Option<PathBuf>,
+}
+
+impl ServeArgs {
+    pub fn run(self, setting: &Setting) -> Result<()> {
+        let config = ServerConfig {
+            addr: SocketAddr::new(self.host, self.port),
+            model: setting.config.model.clone(),
+            body_limit: self.max_body_mb * 1024 * 1024,
+            queue: self.queue,
+            embeddings: self.embeddings,
+            source: self.source,
+        };
+
+        // Commands run synchronously inside the main runtime
+        let handle = tokio::runtime::Handle::current();
+        tokio::task::block_in_place(move || handle.block_on(serve(config)))
+    }
+}
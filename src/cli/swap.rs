@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use crate::{
+    image::{Animation, Image, ImageSequence},
+    model::{data::VectorizedTensor, FaceSelection, Model},
+    setting::Setting,
+    Error, Result,
+};
+
+use super::load_source;
+
+#[derive(clap::Args, Debug)]
+pub struct SwapArgs {
+    /// Image containing source face or `.emb` embedding file
+    #[arg(short, long)]
+    pub source: PathBuf,
+    /// Face of a multi-face source: best, largest, all, an index or indices
+    /// to average (0,2)
+    #[arg(long, default_value = "best")]
+    pub source_face: FaceSelection,
+    /// Image to swap source face into, or a frame directory or pattern
+    /// (shots/shot_%05d.png)
+    #[arg(short, long)]
+    pub target: PathBuf,
+    /// Output image path (animated targets are written as gif), directory
+    /// for frame sequences
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+impl SwapArgs {
+    #[tracing::instrument(name = "Running swap command", skip(setting), err)]
+    pub fn run(self, setting: &Setting) -> Result<()> {
+        let animated =
+            !ImageSequence::is_sequence(&self.target) && Animation::is_animated(&self.target)?;
+        if animated {
+            Animation::check_output(&self.output)?;
+        }
+
+        let mut model = Model::new(&setting.config.model)?;
+        let src = load_source(&mut model, &self.source, &self.source_face)?;
+
+        if ImageSequence::is_sequence(&self.target) {
+            let sequence = ImageSequence::from_path(self.target.clone())?;
+            sequence.ensure_output_dir(&self.output)?;
+            for frame in sequence.frames() {
+                let frame = frame?;
+                let swapped = swap_image(&mut model, frame.image.clone(), &src)?;
+                ImageSequence::write_frame(&frame, &swapped, &self.output)?;
+            }
+            return Ok(());
+        }
+
+        if animated {
+            return Animation::from_path(&self.target)?
+                .try_map(|img| swap_image(&mut model, img, &src))?
+                .save(&self.output);
+        }
+
+        swap_image(&mut model, Image::from_path(self.target, None)?, &src)?
+            .save(&self.output)
+            .map_err(Error::ImageError)
+    }
+}
+
+pub fn swap_image(model: &mut Model, img: Image, src: &VectorizedTensor) -> Result<Image> {
+    Ok(model.run(img.into(), src.clone())?.into())
+}
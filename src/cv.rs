@@ -1,20 +1,22 @@
 use opencv::{core, prelude::*, videoio};
 
 pub use matrix::Matrix;
+pub use video::{VideoReader, VideoWriter};
 
 pub mod matrix;
+pub mod video;
 pub struct CV(videoio::VideoCapture);
 
 // Resolution => 640 x 480
 impl CV {
-    pub fn new() -> crate::Result<Self> {
+    pub fn new(device: i32) -> crate::Result<Self> {
         //https://docs.opencv.org/3.4/d4/d15/group__videoio__flags__base.html
-        let cam =
-            videoio::VideoCapture::new(0, videoio::CAP_DSHOW).map_err(crate::Error::CVError)?;
+        let cam = videoio::VideoCapture::new(device, videoio::CAP_DSHOW)
+            .map_err(crate::Error::CVError)?;
 
         if !cam.is_opened().map_err(crate::Error::CVError)? {
             return Err(crate::Error::UnknownError(
-                "Unable to open default camera".into(),
+                format!("Unable to open camera {}", device).into(),
             ));
         }
 
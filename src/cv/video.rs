@@ -0,0 +1,144 @@
+use std::path::Path;
+
+use opencv::{core, prelude::*, videoio};
+
+use crate::{image::Image, Error, Result};
+
+use super::Matrix;
+
+pub struct VideoReader {
+    capture: videoio::VideoCapture,
+    pub fps: f64,
+    pub frame_count: usize,
+    pub size: (i32, i32),
+}
+
+impl VideoReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let capture = videoio::VideoCapture::from_file(path_str(path)?, videoio::CAP_ANY)
+            .map_err(Error::CVError)?;
+
+        if !capture.is_opened().map_err(Error::CVError)? {
+            return Err(Error::UnknownError(
+                format!("Unable to open video: {}", path.display()).into(),
+            ));
+        }
+
+        let prop = |id: i32| capture.get(id).map_err(Error::CVError);
+        let (fps, frame_count, width, height) = (
+            prop(videoio::CAP_PROP_FPS)?,
+            prop(videoio::CAP_PROP_FRAME_COUNT)?,
+            prop(videoio::CAP_PROP_FRAME_WIDTH)?,
+            prop(videoio::CAP_PROP_FRAME_HEIGHT)?,
+        );
+
+        Ok(Self {
+            // some containers don't report fps
+            fps: if fps > 0. { fps } else { 30. },
+            frame_count: frame_count.max(0.) as usize,
+            size: (width as i32, height as i32),
+            capture,
+        })
+    }
+
+    pub fn next_frame(&mut self) -> Result<Option<Matrix>> {
+        let mut frame = core::Mat::default();
+        if !self.capture.read(&mut frame).map_err(Error::CVError)? || frame.rows() == 0 {
+            return Ok(None);
+        }
+        Ok(Some(frame.into()))
+    }
+}
+
+pub struct VideoWriter(videoio::VideoWriter);
+
+impl VideoWriter {
+    /// Codec follows the container, see `fourcc_for`
+    pub fn create(path: &Path, fps: f64, size: (i32, i32)) -> Result<Self> {
+        let (a, b, c, d) = fourcc_for(path)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(Error::as_unknown_error)?;
+        }
+        let writer = videoio::VideoWriter::new(
+            path_str(path)?,
+            videoio::VideoWriter::fourcc(a, b, c, d).map_err(Error::CVError)?,
+            fps,
+            core::Size::new(size.0, size.1),
+            true,
+        )
+        .map_err(Error::CVError)?;
+
+        if !writer.is_opened().map_err(Error::CVError)? {
+            return Err(Error::UnknownError(
+                format!("Unable to create video: {}", path.display()).into(),
+            ));
+        }
+        Ok(Self(writer))
+    }
+
+    /// BGR matrix
+    pub fn write(&mut self, frame: &Matrix) -> Result<()> {
+        self.0.write(&frame.0).map_err(Error::CVError)
+    }
+
+    pub fn write_image(&mut self, image: &Image) -> Result<()> {
+        let rgb = core::Mat::from_slice(image.as_raw().as_slice())
+            .map_err(Error::CVError)?
+            .reshape(3, image.height() as i32)
+            .map_err(Error::CVError)?
+            .clone_pointee();
+        let mut bgr = core::Mat::default();
+        opencv::imgproc::cvt_color_def(&rgb, &mut bgr, opencv::imgproc::COLOR_RGB2BGR)
+            .map_err(Error::CVError)?;
+        self.0.write(&bgr).map_err(Error::CVError)
+    }
+}
+
+impl Drop for VideoWriter {
+    fn drop(&mut self) {
+        let _ = self.0.release();
+    }
+}
+
+/// mp4v works with opencv default ffmpeg build for mp4 | mov | avi | mkv,
+/// webm only takes vp8 | vp9
+fn fourcc_for(path: &Path) -> Result<(char, char, char, char)> {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    match ext.as_deref() {
+        Some("mp4" | "mov" | "avi" | "mkv") => Ok(('m', 'p', '4', 'v')),
+        Some("webm") => Ok(('V', 'P', '8', '0')),
+        _ => Err(Error::UnknownError(
+            format!("Unsupported video container: {}", path.display()).into(),
+        )),
+    }
+}
+
+fn path_str(path: &Path) -> Result<&str> {
+    path.to_str().ok_or_else(|| {
+        Error::UnknownError(format!("Invalid video path: {}", path.display()).into())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use super::fourcc_for;
+
+    #[test]
+    fn picks_codec_by_container() {
+        assert_eq!(
+            fourcc_for(Path::new("out/clip.MP4")).ok(),
+            Some(('m', 'p', '4', 'v'))
+        );
+        assert_eq!(
+            fourcc_for(Path::new("clip.webm")).ok(),
+            Some(('V', 'P', '8', '0'))
+        );
+        assert!(fourcc_for(Path::new("clip.gif")).is_err());
+        assert!(fourcc_for(Path::new("clip")).is_err());
+    }
+}
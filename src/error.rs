@@ -52,6 +52,22 @@ impl Error {
     {
         Error::UnknownError(Box::new(err))
     }
+
+    /// Process exit code for headless commands (0 is success), 2 is left to
+    /// clap usage errors
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::UnknownError(_) => 1,
+            Error::ConfigError(_) => 10,
+            Error::ImageError(_) => 3,
+            Error::OpenCVError(_) | Error::CVError(_) => 4,
+            Error::ModelError(_) => 5,
+            Error::InvalidModelIOError(_) => 6,
+            Error::CudaError(_) => 7,
+            Error::GuiError(_) => 8,
+            Error::SyncError(_) | Error::GuardError(_) => 9,
+        }
+    }
 }
 
 impl StdError for Error {}
@@ -0,0 +1,421 @@
+//! C api (`capi` feature), header is generated to `target/include/noface.h`
+//!
+//! Every function returns a [`NofaceStatus`], the message of the last error on
+//! the calling thread is available from [`noface_last_error`]. Handles are
+//! thread safe, calls on the same handle are serialized.
+//!
+//! Frames are 8 bit, 3 channel buffers of `height` rows `stride` bytes apart.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::AssertUnwindSafe,
+    path::PathBuf,
+};
+
+use crate::{image::Image, model::Embedding, swapper::Swapper, Error};
+
+/// Opaque swapper handle
+pub struct NofaceSwapper(Swapper);
+
+/// Values are part of the abi, new statuses are only appended
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NofaceStatus {
+    Ok = 0,
+    Unknown = 1,
+    Config = 2,
+    Image = 3,
+    OpenCv = 4,
+    Model = 5,
+    InvalidModelIo = 6,
+    Cuda = 7,
+    Gui = 8,
+    Sync = 9,
+    NullPointer = 10,
+    InvalidArgument = 11,
+    Panic = 12,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NofacePixelFormat {
+    Rgb = 0,
+    Bgr = 1,
+}
+
+#[repr(C)]
+pub struct NofaceFrame {
+    pub data: *mut u8,
+    pub width: u32,
+    pub height: u32,
+    /// Bytes per row, at least `width * 3`
+    pub stride: usize,
+    pub format: NofacePixelFormat,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NofaceFace {
+    pub score: f32,
+    /// x1, y1, x2, y2
+    pub bbox: [f32; 4],
+    /// Eyes, nose and mouth corners as x, y pairs
+    pub keypoints: [f32; 10],
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+struct Failure(NofaceStatus, String);
+
+impl From<Error> for Failure {
+    fn from(err: Error) -> Self {
+        Self(status_of(&err), err.to_string())
+    }
+}
+
+fn status_of(err: &Error) -> NofaceStatus {
+    match err {
+        Error::UnknownError(_) => NofaceStatus::Unknown,
+        Error::ConfigError(_) => NofaceStatus::Config,
+        Error::ImageError(_) => NofaceStatus::Image,
+        Error::OpenCVError(_) | Error::CVError(_) => NofaceStatus::OpenCv,
+        Error::ModelError(_) => NofaceStatus::Model,
+        Error::InvalidModelIOError(_) => NofaceStatus::InvalidModelIo,
+        Error::CudaError(_) => NofaceStatus::Cuda,
+        Error::GuiError(_) => NofaceStatus::Gui,
+        Error::SyncError(_) | Error::GuardError(_) => NofaceStatus::Sync,
+    }
+}
+
+fn set_last_error(msg: String) {
+    let msg = CString::new(msg.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(msg));
+}
+
+/// Runs `f` catching errors and panics into a status
+fn guard(f: impl FnOnce() -> Result<(), Failure>) -> NofaceStatus {
+    let failure = match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => return NofaceStatus::Ok,
+        Ok(Err(failure)) => failure,
+        Err(_) => Failure(NofaceStatus::Panic, "noface panicked".into()),
+    };
+    set_last_error(failure.1);
+    failure.0
+}
+
+fn null_pointer(name: &str) -> Failure {
+    Failure(
+        NofaceStatus::NullPointer,
+        format!("`{}` is a null pointer", name),
+    )
+}
+
+fn invalid_argument(msg: impl Into<String>) -> Failure {
+    Failure(NofaceStatus::InvalidArgument, msg.into())
+}
+
+unsafe fn swapper<'a>(handle: *const NofaceSwapper) -> Result<&'a Swapper, Failure> {
+    handle
+        .as_ref()
+        .map(|handle| &handle.0)
+        .ok_or_else(|| null_pointer("swapper"))
+}
+
+impl NofaceFrame {
+    fn validate(&self) -> Result<usize, Failure> {
+        if self.data.is_null() {
+            return Err(null_pointer("frame.data"));
+        }
+        let row = self.width as usize * 3;
+        if self.width == 0 || self.height == 0 || self.stride < row {
+            return Err(invalid_argument(format!(
+                "Invalid frame layout: {}x{} with stride {}",
+                self.width, self.height, self.stride
+            )));
+        }
+        Ok(row)
+    }
+
+    /// Copies the frame as rgb image
+    unsafe fn to_image(&self) -> Result<Image, Failure> {
+        let row = self.validate()?;
+        let (width, height) = (self.width as usize, self.height as usize);
+        let data = std::slice::from_raw_parts(self.data, self.stride * (height - 1) + row);
+
+        let mut rgb = Vec::with_capacity(row * height);
+        for y in 0..height {
+            let line = &data[y * self.stride..y * self.stride + row];
+            match self.format {
+                NofacePixelFormat::Rgb => rgb.extend_from_slice(line),
+                NofacePixelFormat::Bgr => {
+                    for px in line.chunks_exact(3) {
+                        rgb.extend_from_slice(&[px[2], px[1], px[0]]);
+                    }
+                }
+            }
+        }
+
+        image::RgbImage::from_raw(width as u32, height as u32, rgb)
+            .map(Image::from)
+            .ok_or_else(|| invalid_argument("Frame buffer too small"))
+    }
+
+    /// Writes `image` back into the frame buffer (same dimensions)
+    unsafe fn write_image(&mut self, image: &Image) -> Result<(), Failure> {
+        let row = self.validate()?;
+        if image.dimensions() != (self.width, self.height) {
+            return Err(invalid_argument("Output dimensions don't match frame"));
+        }
+        let height = self.height as usize;
+        let data = std::slice::from_raw_parts_mut(self.data, self.stride * (height - 1) + row);
+
+        for (y, src) in image.as_raw().chunks_exact(row).enumerate() {
+            let line = &mut data[y * self.stride..y * self.stride + row];
+            match self.format {
+                NofacePixelFormat::Rgb => line.copy_from_slice(src),
+                NofacePixelFormat::Bgr => {
+                    for (dst, px) in line.chunks_exact_mut(3).zip(src.chunks_exact(3)) {
+                        dst.copy_from_slice(&[px[2], px[1], px[0]]);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Message of the last failed call on this thread, valid until the next
+/// failing call on the same thread. Null when there was none
+#[no_mangle]
+pub extern "C" fn noface_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+/// Loads models from `models_dir` (utf-8 path, null for `./models`)
+///
+/// # Safety
+/// `models_dir` is null or a nul terminated string, `out` is writable
+#[no_mangle]
+pub unsafe extern "C" fn noface_swapper_new(
+    models_dir: *const c_char,
+    cuda: bool,
+    out: *mut *mut NofaceSwapper,
+) -> NofaceStatus {
+    guard(|| {
+        if out.is_null() {
+            return Err(null_pointer("out"));
+        }
+
+        let mut builder = Swapper::builder().cuda(cuda);
+        if !models_dir.is_null() {
+            let dir = CStr::from_ptr(models_dir)
+                .to_str()
+                .map_err(|_| invalid_argument("`models_dir` is not valid utf-8"))?;
+            builder = builder.models(PathBuf::from(dir));
+        }
+
+        *out = Box::into_raw(Box::new(NofaceSwapper(builder.build()?)));
+        Ok(())
+    })
+}
+
+/// # Safety
+/// `swapper` is null or a handle from [`noface_swapper_new`] not freed yet
+#[no_mangle]
+pub unsafe extern "C" fn noface_swapper_free(swapper: *mut NofaceSwapper) {
+    if !swapper.is_null() {
+        drop(Box::from_raw(swapper));
+    }
+}
+
+/// Sets source identity from the first face of `frame`
+///
+/// # Safety
+/// `swapper` is a live handle, `frame` points to a valid frame
+#[no_mangle]
+pub unsafe extern "C" fn noface_set_source_frame(
+    swapper: *const NofaceSwapper,
+    frame: *const NofaceFrame,
+) -> NofaceStatus {
+    guard(|| {
+        let swapper = self::swapper(swapper)?;
+        let frame = frame.as_ref().ok_or_else(|| null_pointer("frame"))?;
+        swapper.set_source(&frame.to_image()?)?;
+        Ok(())
+    })
+}
+
+/// Sets source identity from `.emb` file bytes
+///
+/// # Safety
+/// `swapper` is a live handle, `data` points to `len` readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn noface_set_source_embedding(
+    swapper: *const NofaceSwapper,
+    data: *const u8,
+    len: usize,
+) -> NofaceStatus {
+    guard(|| {
+        let swapper = self::swapper(swapper)?;
+        if data.is_null() {
+            return Err(null_pointer("data"));
+        }
+        let embedding = Embedding::from_bytes(std::slice::from_raw_parts(data, len))?;
+        swapper.set_identity(swapper.identity_from_embedding(&embedding)?)?;
+        Ok(())
+    })
+}
+
+/// Swaps the source into `frame` in place, frames without faces are untouched
+///
+/// # Safety
+/// `swapper` is a live handle, `frame` points to a valid writable frame
+#[no_mangle]
+pub unsafe extern "C" fn noface_swap_frame(
+    swapper: *const NofaceSwapper,
+    frame: *mut NofaceFrame,
+) -> NofaceStatus {
+    guard(|| {
+        let swapper = self::swapper(swapper)?;
+        let frame = frame.as_mut().ok_or_else(|| null_pointer("frame"))?;
+        let output = swapper.swap(&frame.to_image()?)?;
+        frame.write_image(&output)
+    })
+}
+
+/// Writes up to `capacity` faces (by descending score) into `faces` and the
+/// number of detected faces into `count`, which can exceed `capacity`
+///
+/// # Safety
+/// `swapper` is a live handle, `frame` points to a valid frame, `faces` has
+/// room for `capacity` items (may be null when `capacity` is 0), `count` is writable
+#[no_mangle]
+pub unsafe extern "C" fn noface_detect(
+    swapper: *const NofaceSwapper,
+    frame: *const NofaceFrame,
+    faces: *mut NofaceFace,
+    capacity: usize,
+    count: *mut usize,
+) -> NofaceStatus {
+    guard(|| {
+        let swapper = self::swapper(swapper)?;
+        let frame = frame.as_ref().ok_or_else(|| null_pointer("frame"))?;
+        if count.is_null() {
+            return Err(null_pointer("count"));
+        }
+        if faces.is_null() && capacity > 0 {
+            return Err(null_pointer("faces"));
+        }
+
+        let detected = swapper.detect(&frame.to_image()?)?;
+        *count = detected.len();
+        for (idx, face) in detected.iter().take(capacity).enumerate() {
+            let (x1, y1, x2, y2) = face.bbox;
+            let mut keypoints = [0.; 10];
+            for (dst, [x, y]) in keypoints.chunks_exact_mut(2).zip(face.keypoints.iter()) {
+                dst.copy_from_slice(&[*x, *y]);
+            }
+
+            *faces.add(idx) = NofaceFace {
+                score: face.score,
+                bbox: [x1, y1, x2, y2],
+                keypoints,
+            };
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{image::Image, Error};
+
+    use super::{status_of, NofaceFrame, NofacePixelFormat, NofaceStatus};
+
+    fn frame(data: &mut [u8], stride: usize, format: NofacePixelFormat) -> NofaceFrame {
+        NofaceFrame {
+            data: data.as_mut_ptr(),
+            width: 2,
+            height: 2,
+            stride,
+            format,
+        }
+    }
+
+    #[test]
+    fn reads_padded_bgr_rows() {
+        // 2x2 bgr with 2 padding bytes per row
+        let mut data = [
+            1, 2, 3, 4, 5, 6, 0xEE, 0xEE, //
+            7, 8, 9, 10, 11, 12, 0xEE, 0xEE,
+        ];
+        let bgr = frame(&mut data, 8, NofacePixelFormat::Bgr);
+        let image = unsafe { bgr.to_image() }.unwrap_or_else(|_| panic!("Failed to read frame"));
+        assert_eq!(image.as_raw(), &[3, 2, 1, 6, 5, 4, 9, 8, 7, 12, 11, 10]);
+
+        let rgb = frame(&mut data, 8, NofacePixelFormat::Rgb);
+        let image = unsafe { rgb.to_image() }.unwrap_or_else(|_| panic!("Failed to read frame"));
+        assert_eq!(image.as_raw(), &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+    }
+
+    #[test]
+    fn writes_rows_keeping_padding() {
+        let image = Image::from(
+            image::RgbImage::from_raw(2, 2, vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]).unwrap(),
+        );
+        let mut data = [0xEE; 16];
+        let mut bgr = frame(&mut data, 8, NofacePixelFormat::Bgr);
+        assert!(unsafe { bgr.write_image(&image) }.is_ok());
+        assert_eq!(
+            data,
+            [3, 2, 1, 6, 5, 4, 0xEE, 0xEE, 9, 8, 7, 12, 11, 10, 0xEE, 0xEE]
+        );
+
+        let small = Image::from(image::RgbImage::new(1, 2));
+        let mut rgb = frame(&mut data, 8, NofacePixelFormat::Rgb);
+        let err = unsafe { rgb.write_image(&small) }.err().map(|err| err.0);
+        assert_eq!(err, Some(NofaceStatus::InvalidArgument));
+    }
+
+    #[test]
+    fn rejects_null_and_short_rows() {
+        let mut null = frame(&mut [], 6, NofacePixelFormat::Rgb);
+        null.data = std::ptr::null_mut();
+        let err = unsafe { null.to_image() }.err().map(|err| err.0);
+        assert_eq!(err, Some(NofaceStatus::NullPointer));
+
+        // stride shorter than width * 3
+        let mut data = [0; 10];
+        let short = frame(&mut data, 5, NofacePixelFormat::Rgb);
+        let err = unsafe { short.to_image() }.err().map(|err| err.0);
+        assert_eq!(err, Some(NofaceStatus::InvalidArgument));
+
+        let mut empty = frame(&mut data, 6, NofacePixelFormat::Rgb);
+        empty.height = 0;
+        let err = unsafe { empty.to_image() }.err().map(|err| err.0);
+        assert_eq!(err, Some(NofaceStatus::InvalidArgument));
+    }
+
+    #[test]
+    fn maps_errors_to_statuses() {
+        assert_eq!(
+            status_of(&Error::InvalidModelIOError("no face".into())),
+            NofaceStatus::InvalidModelIo
+        );
+        assert_eq!(
+            status_of(&Error::GuardError("poisoned".into())),
+            NofaceStatus::Sync
+        );
+        assert_eq!(
+            status_of(&Error::UnknownError("unknown".into())),
+            NofaceStatus::Unknown
+        );
+    }
+}
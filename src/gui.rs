@@ -1,23 +1,98 @@
-use std::time::Duration;
-
+use command::{Command, CommandPalette, Keymap};
 use eframe::egui::{self, Button, Color32, Vec2};
+use face_picker::{FacePicker, FacePickerAction};
+use file_drop::{DropZone, DropZones};
+use gallery::{GalleryAction, GalleryWindow};
+use loading::LoadingAction;
 use messenger::{MessageSeverity, Messenger};
-use proc::{ProcStatus, Processor};
+use output_window::{OutputWindow, OutputWindowAction};
+use proc::{DefaultRule, ProcStatus, Processor, RunTarget, TrackRule};
+use run_dialog::{RunDialog, RunDialogAction};
+use settings::{SettingsAction, SettingsWindow};
+use view::{FrameView, ViewMode};
 
 use crate::{error::Error, result::Result, setting::Setting};
 
+mod command;
+mod face_picker;
+mod file_drop;
+mod gallery;
+mod loading;
 mod messenger;
+mod output_window;
+mod overlay;
+mod perf_hud;
 mod proc;
+mod run_dialog;
+mod settings;
+mod view;
 
 pub struct Gui {
     setting: Setting,
     proc: Processor,
     messenger: Messenger,
+    run_dialog: Option<RunDialog>,
+    settings: Option<SettingsWindow>,
+    gallery: Option<GalleryWindow>,
+    face_picker: Option<FacePicker>,
+    frame_view: FrameView,
+    /// Track id of the face the target context menu was opened on
+    target_menu: Option<usize>,
+    drop_zones: DropZones,
+    keymap: Keymap,
+    command_palette: Option<CommandPalette>,
+    /// Stage timings shown over the window while processing
+    perf_hud: bool,
+    output_window: Option<OutputWindow>,
 }
 
 impl eframe::App for Gui {
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
+        for command in self.keymap.pressed(ctx) {
+            self.run_command(command);
+        }
+
+        egui::TopBottomPanel::top("menu").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if self.proc.is_model_loaded() && ui.button("Gallery").clicked() {
+                    self.gallery = match self.gallery {
+                        Some(_) => None,
+                        None => Some(GalleryWindow::default()),
+                    };
+                }
+                if ui
+                    .button("Settings")
+                    .on_hover_text(self.keymap.hint(Command::OpenSettings))
+                    .clicked()
+                {
+                    self.run_command(Command::OpenSettings);
+                }
+                if ui.button(self.messenger.history_label()).clicked() {
+                    self.messenger.toggle_history();
+                }
+                if ui
+                    .add(Button::new("Output").selected(self.output_window.is_some()))
+                    .on_hover_text(self.keymap.hint(Command::ToggleOutputWindow))
+                    .clicked()
+                {
+                    self.run_command(Command::ToggleOutputWindow);
+                }
+                if ui
+                    .button("Commands")
+                    .on_hover_text(self.keymap.hint(Command::CommandPalette))
+                    .clicked()
+                {
+                    self.run_command(Command::CommandPalette);
+                }
+            });
+        });
+
         egui::CentralPanel::default().show(ctx, |ui| {
+            if !self.proc.is_model_loaded() {
+                let action = loading::show(ui, &self.proc.get_load_state());
+                self.handle_loading_action(action);
+                return;
+            }
             let proc_status = self.proc.get_status();
             // Main Control
             ui.horizontal(|ui| {
@@ -29,14 +104,12 @@ impl eframe::App for Gui {
                     )
                     .min_size(button_size);
 
-                    if ui
-                        .add_enabled(
-                            proc_status != ProcStatus::Previewing
-                                && proc_status != ProcStatus::Running,
-                            image_button,
-                        )
-                        .clicked()
-                    {
+                    let source_btn = ui.add_enabled(
+                        proc_status != ProcStatus::Previewing && proc_status != ProcStatus::Running,
+                        image_button,
+                    );
+                    self.drop_zones.source = Some(source_btn.rect);
+                    if source_btn.clicked() {
                         let Some(path) = rfd::FileDialog::new().pick_file() else {
                             self.messenger
                                 .send_message("No files selected", Some(MessageSeverity::Warning));
@@ -76,65 +149,206 @@ impl eframe::App for Gui {
                         ),
                     );
 
-                    if run_btn.clicked() {
-                        println!("run clicked");
+                    if run_btn
+                        .on_hover_text(self.keymap.hint(Command::Run))
+                        .clicked()
+                    {
+                        self.run_command(Command::Run);
                     }
-
-                    if preview_btn.clicked() {
-                        if proc_status == ProcStatus::Idle {
-                            if let Err(error) = self.proc.run_preview() {
-                                self.messenger.send_message(
-                                    format!("Failed to run with: {}", error),
-                                    Some(MessageSeverity::Error),
-                                );
-                            }
-                        } else {
-                            let _ = self.proc.stop();
-                        }
+                    if preview_btn
+                        .on_hover_text(self.keymap.hint(Command::TogglePreview))
+                        .clicked()
+                    {
+                        self.run_command(Command::TogglePreview);
                     }
                 });
             });
 
             // Image Display
-            egui::Frame::none()
+            let display = egui::Frame::none()
                 .rounding(3.)
                 .stroke(egui::Stroke::new(1., Color32::WHITE))
                 .outer_margin(egui::Margin::symmetric(0., 8.))
                 .inner_margin(egui::Margin::same(2.))
                 .show(ui, |ui| match proc_status {
                     ProcStatus::Running => {
-                        ui.add_sized(
-                            ui.available_size(),
-                            egui::Label::new("Not yet implemented: Run"),
-                        );
+                        let progress = self.proc.get_run_progress();
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .button(if progress.paused { "Resume" } else { "Pause" })
+                                    .clicked()
+                                {
+                                    let _ = self.proc.toggle_pause();
+                                }
+                                let bar = match (progress.fraction(), progress.total) {
+                                    (Some(fraction), Some(total)) => {
+                                        egui::ProgressBar::new(fraction)
+                                            .text(format!("{} / {}", progress.done, total))
+                                    }
+                                    _ => egui::ProgressBar::new(0.)
+                                        .animate(!progress.paused)
+                                        .text(format!("{} frames", progress.done)),
+                                };
+                                ui.add(bar);
+                            });
+
+                            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                                self.show_frames(ui);
+                            });
+                        });
+                        ctx.request_repaint()
                     }
                     ProcStatus::Previewing => {
-                        let Ok(tex) = self.proc.get_frame().inspect_err(|err| {
-                            self.messenger.send_message(
-                                format!("Preview failed with: {}", err),
-                                Some(MessageSeverity::Error),
-                            );
-                        }) else {
-                            return;
-                        };
-                        ui.add_sized(
-                            ui.available_size(),
-                            egui::Image::from_texture(egui::load::SizedTexture::from_handle(&tex))
-                                .max_size(ui.available_size()),
-                        );
+                        ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
+                            self.show_capture_controls(ui);
+                            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                                self.show_frames(ui);
+                            });
+                        });
                         ctx.request_repaint()
                     }
                     // TODO: Might want Error state msg
                     _ => {
                         ui.add_sized(
                             ui.available_size(),
-                            egui::Label::new("Not yet impleted: Rest"),
+                            egui::Label::new("Drop a target image or video here"),
                         );
                     }
                 });
+            self.drop_zones.preview = Some(display.response.rect);
         });
 
-        let _ = self.messenger.register_messenger(ctx);
+        if self.perf_hud
+            && matches!(
+                self.proc.get_status(),
+                ProcStatus::Previewing | ProcStatus::Running
+            )
+        {
+            perf_hud::show(ctx, &self.proc.get_perf());
+        }
+
+        if let (Some(window), Ok(output)) = (self.output_window.as_mut(), self.proc.get_frame()) {
+            let [r, g, b] = self.setting.config.gui.output_window.background();
+            match window.show(ctx, &output, Color32::from_rgb(r, g, b)) {
+                OutputWindowAction::Close => self.output_window = None,
+                OutputWindowAction::None => {}
+            }
+        }
+
+        if self.proc.is_model_loaded() {
+            self.drop_zones.paint_hover(ctx);
+            if let Some((zone, path)) = self.drop_zones.take_dropped(ctx) {
+                self.handle_drop(zone, path);
+            }
+        }
+
+        if let Some(palette) = self.command_palette.as_mut() {
+            if let Some(command) = palette.show(ctx, &self.keymap) {
+                self.command_palette = None;
+                if let Some(command) = command {
+                    self.run_command(command);
+                }
+            }
+        }
+
+        if let Some(dialog) = self.run_dialog.as_mut() {
+            match dialog.show(ctx, &self.setting.config.gui.output) {
+                RunDialogAction::Start(target, output) => {
+                    self.run_dialog = None;
+                    if let Err(err) = self.proc.run(target, output) {
+                        self.messenger.send_message(
+                            format!("Failed to run with: {}", err),
+                            Some(MessageSeverity::Error),
+                        );
+                    }
+                }
+                RunDialogAction::Close => self.run_dialog = None,
+                RunDialogAction::None => {}
+            }
+        }
+
+        if let Some(window) = self.settings.as_mut() {
+            let editable = matches!(
+                self.proc.get_status(),
+                ProcStatus::Idle | ProcStatus::NotInitialized
+            );
+            match window.show(ctx, editable) {
+                SettingsAction::Apply(config) => {
+                    self.settings = None;
+                    self.apply_config(config);
+                }
+                SettingsAction::Close => self.settings = None,
+                SettingsAction::None => {}
+            }
+        }
+
+        if let Some(window) = self.gallery.as_mut() {
+            let (entries, active) = self.proc.get_gallery_entries();
+            let gallery = std::sync::Arc::clone(&self.proc.gallery);
+            let action = window.show(
+                ctx,
+                &entries,
+                active.as_deref(),
+                self.proc.get_status() == ProcStatus::Idle,
+                |id| gallery.read().ok()?.thumbnail(id),
+            );
+            self.handle_gallery_action(action);
+        }
+
+        if let Some(candidates) = self.proc.take_source_candidates() {
+            self.face_picker = Some(FacePicker::new(ctx, candidates));
+        }
+        if let Some(picker) = self.face_picker.as_mut() {
+            match picker.show(ctx) {
+                FacePickerAction::Choose(indices) => {
+                    if let Some(picker) = self.face_picker.take() {
+                        if let Err(err) = self
+                            .proc
+                            .choose_source_faces(picker.into_candidates(), indices)
+                        {
+                            self.messenger
+                                .send_message(err.to_string(), Some(MessageSeverity::Error));
+                        }
+                    }
+                }
+                FacePickerAction::Cancel => self.face_picker = None,
+                FacePickerAction::None => {}
+            }
+        }
+
+        match self.proc.take_snapshot_result() {
+            Some(Ok(path)) => self.messenger.send_message(
+                format!("Saved snapshot to {}", path.display()),
+                Some(MessageSeverity::Info),
+            ),
+            Some(Err(err)) => self.messenger.send_message(
+                format!("Failed to save snapshot: {}", err),
+                Some(MessageSeverity::Error),
+            ),
+            None => {}
+        }
+
+        if let Some(report) = self.proc.take_run_report() {
+            if report.cancelled {
+                self.messenger.send_message(
+                    format!("Run cancelled after {} frames", report.frames),
+                    Some(MessageSeverity::Warning),
+                );
+            } else {
+                self.messenger.send_message(
+                    format!(
+                        "Saved {} frames to {}",
+                        report.frames,
+                        report.output.display()
+                    ),
+                    Some(MessageSeverity::Info),
+                );
+            }
+        }
+
+        self.messenger.register_messenger(ctx);
+        self.messenger.show_history(ctx);
 
         let _ = self.proc.register_error(|err| {
             self.messenger
@@ -149,11 +363,385 @@ impl Gui {
     #[tracing::instrument(name = "Initializing Gui", skip(setting))]
     pub fn new(setting: Setting) -> Self {
         let config = setting.config.clone();
+        let (keymap, hotkey_errors) = Keymap::from_config(&config.gui.hotkeys);
+        let mut messenger = Messenger::default();
+        for err in hotkey_errors {
+            messenger.send_message(format!("Hotkeys - {}", err), Some(MessageSeverity::Warning));
+        }
         Self {
             setting,
-            //TODO: Load these after inital render with loading
-            proc: Processor::new(&config).unwrap(),
-            messenger: Messenger::new(Duration::from_millis(2000)),
+            proc: Processor::new(&config),
+            messenger,
+            run_dialog: None,
+            settings: None,
+            gallery: None,
+            face_picker: None,
+            frame_view: FrameView::default(),
+            target_menu: None,
+            drop_zones: DropZones::default(),
+            keymap,
+            command_palette: None,
+            perf_hud: false,
+            output_window: None,
+        }
+    }
+
+    fn handle_drop(&mut self, zone: DropZone, path: std::path::PathBuf) {
+        if !zone.accepts(&path) {
+            let expected = match zone {
+                DropZone::Source => "a face image or .emb file",
+                DropZone::Preview => "an image or video",
+            };
+            self.messenger.send_message(
+                format!("Unsupported file, expected {}", expected),
+                Some(MessageSeverity::Warning),
+            );
+            return;
+        }
+        if self.proc.get_status() != ProcStatus::Idle {
+            self.messenger.send_message(
+                "Stop the current preview or run before dropping files",
+                Some(MessageSeverity::Warning),
+            );
+            return;
+        }
+
+        match zone {
+            DropZone::Source => {
+                if let Err(err) = self.proc.set_source_with_path(path) {
+                    self.messenger
+                        .send_message(err.to_string(), Some(MessageSeverity::Error));
+                }
+            }
+            DropZone::Preview => {
+                self.run_dialog = Some(RunDialog::with_target(RunTarget::File(path)));
+            }
+        }
+    }
+
+    fn handle_gallery_action(&mut self, action: GalleryAction) {
+        let result = match action {
+            GalleryAction::None => Ok(()),
+            GalleryAction::Close => {
+                self.gallery = None;
+                Ok(())
+            }
+            GalleryAction::Add(paths) => paths
+                .into_iter()
+                .try_for_each(|path| self.proc.set_source_with_path(path)),
+            GalleryAction::Select(id) => self.proc.select_gallery_source(id),
+            GalleryAction::Rename(id, name) => self.proc.rename_gallery_source(&id, name),
+            GalleryAction::Remove(id) => self.proc.remove_gallery_source(&id),
+        };
+        if let Err(err) = result {
+            self.messenger
+                .send_message(format!("Gallery - {}", err), Some(MessageSeverity::Error));
+        }
+    }
+
+    /// Retries the model load, from another models folder when one was picked
+    fn handle_loading_action(&mut self, action: LoadingAction) {
+        match action {
+            LoadingAction::Retry => {
+                if let Err(err) = self.proc.load_model(self.setting.config.model.clone()) {
+                    self.messenger
+                        .send_message(err.to_string(), Some(MessageSeverity::Error));
+                }
+            }
+            LoadingAction::UseModelsDir(dir) => {
+                let mut config = self.setting.config.clone();
+                config.model.dir = Some(dir);
+                self.apply_config(config);
+            }
+            LoadingAction::None => {}
+        }
+    }
+
+    /// Saves `config` and applies it, reloading models when sessions changed
+    fn apply_config(&mut self, mut config: crate::setting::Config) {
+        // window size is tracked separately by `update_dim`
+        config.gui.width = self.setting.config.gui.width;
+        config.gui.height = self.setting.config.gui.height;
+
+        let (keymap, hotkey_errors) = Keymap::from_config(&config.gui.hotkeys);
+        self.keymap = keymap;
+        for err in hotkey_errors {
+            self.messenger
+                .send_message(format!("Hotkeys - {}", err), Some(MessageSeverity::Warning));
+        }
+        // a failed load is retried with whatever changed
+        let needs_reload =
+            self.setting.config.model.needs_reload(&config.model) || !self.proc.is_model_loaded();
+        self.setting.config = config.clone();
+        self.setting.update_config_file();
+
+        let applied = match self.proc.apply_config(&config) {
+            Ok(()) if needs_reload => self.proc.load_model(config.model),
+            applied => applied,
+        };
+        match applied {
+            Ok(()) => self.messenger.send_message(
+                if needs_reload {
+                    "Settings saved, reloading models"
+                } else {
+                    "Settings saved"
+                },
+                Some(MessageSeverity::Info),
+            ),
+            Err(err) => self.messenger.send_message(
+                format!("Failed to apply settings: {}", err),
+                Some(MessageSeverity::Error),
+            ),
+        }
+    }
+
+    /// View mode selector and compared frames, returns the output frame rect
+    fn show_frames(&mut self, ui: &mut egui::Ui) -> Option<egui::Rect> {
+        let (original, output) = match (self.proc.get_original_frame(), self.proc.get_frame()) {
+            (Ok(original), Ok(output)) => (original, output),
+            (Err(err), _) | (_, Err(err)) => {
+                self.messenger.send_message(
+                    format!("Preview failed with: {}", err),
+                    Some(MessageSeverity::Error),
+                );
+                return None;
+            }
+        };
+        self.frame_view.show_mode_selector(ui);
+        let rect = self.frame_view.show(ui, &original, &output)?;
+        let info = self.proc.get_frame_info();
+        if self.frame_view.overlay {
+            overlay::paint(ui, rect, &info, &self.proc.get_target_rules());
+        }
+        // split view drags its divider instead
+        if self.frame_view.mode != ViewMode::Split {
+            self.select_targets(ui, rect, &info);
+        }
+        Some(rect)
+    }
+
+    fn show_capture_controls(&mut self, ui: &mut egui::Ui) {
+        let recording = self.proc.is_recording();
+        ui.horizontal(|ui| {
+            if ui
+                .button("Snapshot")
+                .on_hover_text(self.keymap.hint(Command::Snapshot))
+                .clicked()
+            {
+                self.run_command(Command::Snapshot);
+            }
+            if ui
+                .add(
+                    Button::new(if recording {
+                        "Stop recording"
+                    } else {
+                        "Record"
+                    })
+                    .selected(recording),
+                )
+                .on_hover_text(self.keymap.hint(Command::Record))
+                .clicked()
+            {
+                self.run_command(Command::Record);
+            }
+            if recording {
+                ui.colored_label(Color32::RED, "\u{25cf} REC");
+            }
+        });
+    }
+
+    /// Commands only act when the current state allows them, like their
+    /// buttons
+    fn run_command(&mut self, command: Command) {
+        let status = self.proc.get_status();
+        match command {
+            Command::TogglePreview => match status {
+                ProcStatus::Idle => {
+                    if let Err(error) = self.proc.run_preview() {
+                        self.messenger.send_message(
+                            format!("Failed to run with: {}", error),
+                            Some(MessageSeverity::Error),
+                        );
+                    }
+                }
+                ProcStatus::Previewing => {
+                    self.stop_recording();
+                    let _ = self.proc.stop();
+                }
+                _ => {}
+            },
+            Command::Run => match status {
+                ProcStatus::Idle => self.run_dialog = Some(RunDialog::default()),
+                ProcStatus::Running => {
+                    let _ = self.proc.cancel_run();
+                }
+                _ => {}
+            },
+            Command::Snapshot if status == ProcStatus::Previewing => {
+                if let Err(err) = self.proc.request_snapshot() {
+                    self.messenger.send_message(
+                        format!("Failed to save snapshot: {}", err),
+                        Some(MessageSeverity::Error),
+                    );
+                }
+            }
+            Command::Record if status == ProcStatus::Previewing => {
+                if self.proc.is_recording() {
+                    self.stop_recording();
+                } else if let Err(err) = self.proc.start_recording() {
+                    self.messenger.send_message(
+                        format!("Failed to start recording: {}", err),
+                        Some(MessageSeverity::Error),
+                    );
+                }
+            }
+            Command::ToggleOverlay => self.frame_view.overlay = !self.frame_view.overlay,
+            Command::TogglePerfHud => self.perf_hud = !self.perf_hud,
+            Command::ToggleOutputWindow => {
+                self.output_window = match self.output_window {
+                    Some(_) => None,
+                    None => Some(OutputWindow::default()),
+                };
+            }
+            Command::NextSource | Command::PreviousSource
+                if self.proc.is_model_loaded()
+                    && matches!(status, ProcStatus::Idle | ProcStatus::NotInitialized) =>
+            {
+                self.cycle_source(command == Command::NextSource);
+            }
+            Command::OpenSettings => {
+                if self.settings.is_none() {
+                    self.settings = Some(SettingsWindow::new(&self.setting.config));
+                }
+            }
+            Command::CommandPalette => {
+                self.command_palette = match self.command_palette {
+                    Some(_) => None,
+                    None => Some(CommandPalette::default()),
+                };
+            }
+            // not available in the current state
+            Command::Snapshot | Command::Record | Command::NextSource | Command::PreviousSource => {
+            }
+        }
+    }
+
+    /// Activates the gallery entry after (or before) the active one
+    fn cycle_source(&mut self, forward: bool) {
+        let (entries, active) = self.proc.get_gallery_entries();
+        if entries.is_empty() {
+            return;
+        }
+        let len = entries.len();
+        let current = entries
+            .iter()
+            .position(|entry| Some(&entry.id) == active.as_ref());
+        let next = match (forward, current) {
+            (true, Some(idx)) => (idx + 1) % len,
+            (true, None) => 0,
+            (false, Some(idx)) => (idx + len - 1) % len,
+            (false, None) => len - 1,
+        };
+        if current != Some(next) {
+            let id = entries[next].id.clone();
+            self.handle_gallery_action(GalleryAction::Select(Some(id)));
+        }
+    }
+
+    fn stop_recording(&mut self) {
+        match self.proc.stop_recording() {
+            Ok(Some(report)) => self.messenger.send_message(
+                format!(
+                    "Recorded {} frames to {}",
+                    report.frames,
+                    report.path.display()
+                ),
+                Some(MessageSeverity::Info),
+            ),
+            Ok(None) => {}
+            Err(err) => self.messenger.send_message(
+                format!("Failed to stop recording: {}", err),
+                Some(MessageSeverity::Error),
+            ),
+        }
+    }
+
+    /// Click toggles swapping of a tracked face, right click assigns it a
+    /// gallery source
+    fn select_targets(&mut self, ui: &mut egui::Ui, rect: egui::Rect, info: &proc::FrameInfo) {
+        let response = ui.interact(rect, ui.id().with("target_select"), egui::Sense::click());
+        let face_at = |pos: Option<egui::Pos2>| {
+            let idx = info.face_at(rect, pos?)?;
+            info.faces[idx].track_id
+        };
+
+        if response.hovered() {
+            let (entries, _) = self.proc.get_gallery_entries();
+            overlay::paint_targets(ui, rect, info, &self.proc.get_target_rules(), |id| {
+                entries
+                    .iter()
+                    .find(|entry| entry.id == id)
+                    .map(|entry| entry.name.clone())
+            });
+            if face_at(response.hover_pos()).is_some() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
+        }
+        if response.clicked() {
+            if let Some(track_id) = face_at(response.interact_pointer_pos()) {
+                let _ = self.proc.toggle_target(track_id);
+            }
+        }
+        if response.secondary_clicked() {
+            self.target_menu = face_at(response.interact_pointer_pos());
+        }
+
+        let Some(track_id) = self.target_menu else {
+            return;
+        };
+        let mut assigned = None;
+        response.context_menu(|ui| {
+            ui.label(format!("Face #{}", track_id));
+            for (label, rule) in [
+                ("Swap with active source", TrackRule::Swap),
+                ("Skip", TrackRule::Skip),
+            ] {
+                if ui.button(label).clicked() {
+                    assigned = Some(self.proc.set_target_rule(track_id, rule));
+                    ui.close_menu();
+                }
+            }
+            let (entries, _) = self.proc.get_gallery_entries();
+            if !entries.is_empty() {
+                ui.separator();
+            }
+            for entry in entries {
+                if ui.button(&entry.name).clicked() {
+                    assigned = Some(self.proc.assign_target_source(track_id, entry.id));
+                    ui.close_menu();
+                }
+            }
+            ui.separator();
+            let mut default = self.proc.get_target_rules().default_rule();
+            ui.label("Unassigned faces");
+            let changed = ui
+                .horizontal(|ui| {
+                    ui.radio_value(&mut default, DefaultRule::Swap, "Swap")
+                        .changed()
+                        | ui.radio_value(&mut default, DefaultRule::Keep, "Keep")
+                            .changed()
+                })
+                .inner;
+            if changed {
+                assigned = Some(self.proc.set_default_target_rule(default));
+                ui.close_menu();
+            }
+        });
+        if let Some(Err(err)) = assigned {
+            self.messenger.send_message(
+                format!("Failed to assign source: {}", err),
+                Some(MessageSeverity::Error),
+            );
         }
     }
 
@@ -176,6 +764,8 @@ impl Gui {
                 // register
                 // TODO: Handle Err
                 let _ = self.proc.register(&cc.egui_ctx);
+                // models load in the worker, the window shows progress meanwhile
+                let _ = self.proc.load_model(self.setting.config.model.clone());
 
                 egui_extras::install_image_loaders(&cc.egui_ctx);
                 Ok(Box::new(self))
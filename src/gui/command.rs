@@ -0,0 +1,358 @@
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+
+use crate::setting::HotkeyConfig;
+
+/// Gui actions reachable from hotkeys and the command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    TogglePreview,
+    Run,
+    Snapshot,
+    Record,
+    ToggleOverlay,
+    TogglePerfHud,
+    ToggleOutputWindow,
+    NextSource,
+    PreviousSource,
+    OpenSettings,
+    CommandPalette,
+}
+
+impl Command {
+    pub const ALL: [Command; 11] = [
+        Command::TogglePreview,
+        Command::Run,
+        Command::Snapshot,
+        Command::Record,
+        Command::ToggleOverlay,
+        Command::TogglePerfHud,
+        Command::ToggleOutputWindow,
+        Command::NextSource,
+        Command::PreviousSource,
+        Command::OpenSettings,
+        Command::CommandPalette,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Command::TogglePreview => "Start / stop preview",
+            Command::Run => "Run / stop run",
+            Command::Snapshot => "Save snapshot",
+            Command::Record => "Start / stop recording",
+            Command::ToggleOverlay => "Toggle debug overlay",
+            Command::TogglePerfHud => "Toggle performance HUD",
+            Command::ToggleOutputWindow => "Open / close output window",
+            Command::NextSource => "Next gallery source",
+            Command::PreviousSource => "Previous gallery source",
+            Command::OpenSettings => "Open settings",
+            Command::CommandPalette => "Command palette",
+        }
+    }
+
+    pub fn binding<'a>(&self, hotkeys: &'a HotkeyConfig) -> &'a str {
+        match self {
+            Command::TogglePreview => &hotkeys.preview,
+            Command::Run => &hotkeys.run,
+            Command::Snapshot => &hotkeys.snapshot,
+            Command::Record => &hotkeys.record,
+            Command::ToggleOverlay => &hotkeys.overlay,
+            Command::TogglePerfHud => &hotkeys.perf_hud,
+            Command::ToggleOutputWindow => &hotkeys.output_window,
+            Command::NextSource => &hotkeys.next_source,
+            Command::PreviousSource => &hotkeys.previous_source,
+            Command::OpenSettings => &hotkeys.settings,
+            Command::CommandPalette => &hotkeys.palette,
+        }
+    }
+
+    pub fn binding_mut<'a>(&self, hotkeys: &'a mut HotkeyConfig) -> &'a mut String {
+        match self {
+            Command::TogglePreview => &mut hotkeys.preview,
+            Command::Run => &mut hotkeys.run,
+            Command::Snapshot => &mut hotkeys.snapshot,
+            Command::Record => &mut hotkeys.record,
+            Command::ToggleOverlay => &mut hotkeys.overlay,
+            Command::TogglePerfHud => &mut hotkeys.perf_hud,
+            Command::ToggleOutputWindow => &mut hotkeys.output_window,
+            Command::NextSource => &mut hotkeys.next_source,
+            Command::PreviousSource => &mut hotkeys.previous_source,
+            Command::OpenSettings => &mut hotkeys.settings,
+            Command::CommandPalette => &mut hotkeys.palette,
+        }
+    }
+}
+
+/// `Ctrl+Shift+S`, `Alt+F4` or `Space`, `None` for an empty binding.
+/// `Ctrl` is `Cmd` on mac
+pub fn parse_shortcut(text: &str) -> Result<Option<KeyboardShortcut>, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let mut parts = text.split('+').map(str::trim).collect::<Vec<_>>();
+    let key_name = parts.pop().unwrap_or_default();
+    let mut modifiers = Modifiers::NONE;
+    for part in parts {
+        modifiers = modifiers
+            | match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" | "cmd" | "command" => Modifiers::COMMAND,
+                "shift" => Modifiers::SHIFT,
+                "alt" | "option" => Modifiers::ALT,
+                _ => return Err(format!("Unknown modifier {} in {}", part, text)),
+            };
+    }
+    let key = Key::from_name(key_name)
+        .or_else(|| Key::from_name(&key_name.to_ascii_uppercase()))
+        .ok_or_else(|| format!("Unknown key {} in {}", key_name, text))?;
+    Ok(Some(KeyboardShortcut::new(modifiers, key)))
+}
+
+/// Inverse of `parse_shortcut`
+pub fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    let mut parts = vec![];
+    if shortcut.modifiers.command || shortcut.modifiers.ctrl || shortcut.modifiers.mac_cmd {
+        parts.push("Ctrl");
+    }
+    if shortcut.modifiers.shift {
+        parts.push("Shift");
+    }
+    if shortcut.modifiers.alt {
+        parts.push("Alt");
+    }
+    parts.push(shortcut.logical_key.name());
+    parts.join("+")
+}
+
+/// Parsed hotkeys, invalid or conflicting bindings are left out
+#[derive(Debug, Default)]
+pub struct Keymap {
+    bindings: Vec<(Command, KeyboardShortcut)>,
+}
+
+impl Keymap {
+    /// Keymap with the valid bindings and the problems with the others
+    pub fn from_config(hotkeys: &HotkeyConfig) -> (Self, Vec<String>) {
+        let mut bindings: Vec<(Command, KeyboardShortcut)> = vec![];
+        let mut errors = vec![];
+        for command in Command::ALL {
+            match parse_shortcut(command.binding(hotkeys)) {
+                Ok(Some(shortcut)) => match bindings.iter().find(|(_, bound)| *bound == shortcut) {
+                    Some((other, _)) => errors.push(format!(
+                        "{} is bound to both {} and {}",
+                        format_shortcut(&shortcut),
+                        other.label(),
+                        command.label()
+                    )),
+                    None => bindings.push((command, shortcut)),
+                },
+                Ok(None) => {}
+                Err(err) => errors.push(err),
+            }
+        }
+        (Self { bindings }, errors)
+    }
+
+    pub fn shortcut(&self, command: Command) -> Option<&KeyboardShortcut> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == command)
+            .map(|(_, shortcut)| shortcut)
+    }
+
+    /// Hover text of the buttons running `command`
+    pub fn hint(&self, command: Command) -> String {
+        match self.shortcut(command) {
+            Some(shortcut) => format!("Shortcut: {}", format_shortcut(shortcut)),
+            None => "No shortcut".into(),
+        }
+    }
+
+    /// Commands whose shortcut was pressed this frame, nothing while a text
+    /// field has focus
+    pub fn pressed(&self, ctx: &egui::Context) -> Vec<Command> {
+        if ctx.wants_keyboard_input() {
+            return vec![];
+        }
+        // shortcuts with more modifiers first, so Ctrl+Shift+S isn't taken by Ctrl+S
+        let mut bindings = self.bindings.iter().collect::<Vec<_>>();
+        bindings.sort_by_key(|(_, shortcut)| {
+            std::cmp::Reverse(
+                [
+                    shortcut.modifiers.command,
+                    shortcut.modifiers.shift,
+                    shortcut.modifiers.alt,
+                ]
+                .into_iter()
+                .filter(|held| *held)
+                .count(),
+            )
+        });
+        ctx.input_mut(|input| {
+            bindings
+                .into_iter()
+                .filter(|(_, shortcut)| input.consume_shortcut(shortcut))
+                .map(|(command, _)| *command)
+                .collect()
+        })
+    }
+}
+
+/// Filterable list of commands, Enter runs the highlighted one
+#[derive(Default)]
+pub struct CommandPalette {
+    filter: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// `Some(None)` when closed without a command
+    pub fn show(&mut self, ctx: &egui::Context, keymap: &Keymap) -> Option<Option<Command>> {
+        let matches = filter_commands(&self.filter);
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+        let mut result = None;
+
+        egui::Window::new("Commands")
+            .collapsible(false)
+            .resizable(false)
+            .title_bar(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::Vec2::new(0., 40.))
+            .show(ctx, |ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.filter)
+                        .hint_text("Type a command")
+                        .desired_width(260.),
+                );
+                response.request_focus();
+
+                let (up, down, enter, escape) = ui.input(|i| {
+                    (
+                        i.key_pressed(Key::ArrowUp),
+                        i.key_pressed(Key::ArrowDown),
+                        i.key_pressed(Key::Enter),
+                        i.key_pressed(Key::Escape),
+                    )
+                });
+                if up {
+                    self.selected = self.selected.saturating_sub(1);
+                }
+                if down && self.selected + 1 < matches.len() {
+                    self.selected += 1;
+                }
+                if escape {
+                    result = Some(None);
+                }
+                if enter {
+                    result = Some(matches.get(self.selected).copied());
+                }
+
+                ui.separator();
+                if matches.is_empty() {
+                    ui.label("No matching command");
+                }
+                for (idx, command) in matches.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .selectable_label(idx == self.selected, command.label())
+                            .clicked()
+                        {
+                            result = Some(Some(*command));
+                        }
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if let Some(shortcut) = keymap.shortcut(*command) {
+                                ui.weak(format_shortcut(shortcut));
+                            }
+                        });
+                    });
+                }
+            });
+        result
+    }
+}
+
+/// Commands whose label contains every word of `filter`, ignoring case
+fn filter_commands(filter: &str) -> Vec<Command> {
+    let words = filter
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>();
+    Command::ALL
+        .into_iter()
+        // the palette doesn't list itself
+        .filter(|command| *command != Command::CommandPalette)
+        .filter(|command| {
+            let label = command.label().to_lowercase();
+            words.iter().all(|word| label.contains(word.as_str()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+
+    use crate::setting::HotkeyConfig;
+
+    use super::{filter_commands, format_shortcut, parse_shortcut, Command, Keymap};
+
+    #[test]
+    fn parses_shortcuts() {
+        assert_eq!(
+            parse_shortcut("Ctrl+Shift+S"),
+            Ok(Some(KeyboardShortcut::new(
+                Modifiers::COMMAND | Modifiers::SHIFT,
+                Key::S
+            )))
+        );
+        assert_eq!(
+            parse_shortcut(" alt + f4 "),
+            Ok(Some(KeyboardShortcut::new(Modifiers::ALT, Key::F4)))
+        );
+        assert_eq!(
+            parse_shortcut("Space"),
+            Ok(Some(KeyboardShortcut::new(Modifiers::NONE, Key::Space)))
+        );
+        assert_eq!(parse_shortcut(""), Ok(None));
+        assert!(parse_shortcut("Hyper+S").is_err());
+        assert!(parse_shortcut("Ctrl+Nope").is_err());
+    }
+
+    #[test]
+    fn formats_parsed_shortcuts_back() {
+        for text in ["Ctrl+S", "Ctrl+Shift+P", "F5", "Alt+Right"] {
+            let shortcut = parse_shortcut(text).unwrap().unwrap();
+            assert_eq!(format_shortcut(&shortcut), text);
+        }
+    }
+
+    #[test]
+    fn default_hotkeys_are_valid_and_unique() {
+        let (keymap, errors) = Keymap::from_config(&HotkeyConfig::default());
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(keymap.shortcut(Command::Snapshot).is_some());
+    }
+
+    #[test]
+    fn reports_conflicts_and_unbinds() {
+        let hotkeys = HotkeyConfig {
+            record: "ctrl+s".into(),
+            overlay: String::new(),
+            ..Default::default()
+        };
+        let (keymap, errors) = Keymap::from_config(&hotkeys);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("Save snapshot"));
+        assert!(keymap.shortcut(Command::Record).is_none());
+        assert!(keymap.shortcut(Command::ToggleOverlay).is_none());
+    }
+
+    #[test]
+    fn filters_commands_by_words() {
+        assert_eq!(
+            filter_commands("gallery prev"),
+            vec![Command::PreviousSource]
+        );
+        assert_eq!(filter_commands("").len(), Command::ALL.len() - 1);
+        assert!(filter_commands("palette").is_empty());
+    }
+}
@@ -0,0 +1,128 @@
+use eframe::egui::{self, Align2, Color32, TextureHandle, Vec2};
+
+use super::proc::SourceCandidates;
+
+const CROP_SIZE: Vec2 = Vec2::splat(80.);
+
+pub enum FacePickerAction {
+    None,
+    Cancel,
+    /// Candidate indices, averaged when several
+    Choose(Vec<usize>),
+}
+
+/// Asks which face of a group photo becomes the source
+pub struct FacePicker {
+    candidates: SourceCandidates,
+    crops: Vec<TextureHandle>,
+    selected: Vec<bool>,
+}
+
+impl FacePicker {
+    pub fn new(ctx: &egui::Context, candidates: SourceCandidates) -> Self {
+        let crops = candidates
+            .faces
+            .iter()
+            .enumerate()
+            .map(|(idx, face)| {
+                ctx.load_texture(
+                    format!("face_picker_{}", idx),
+                    face.tensor.clone(),
+                    Default::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let mut selected = vec![false; crops.len()];
+        // detections are ordered by score
+        if let Some(first) = selected.first_mut() {
+            *first = true;
+        }
+        Self {
+            candidates,
+            crops,
+            selected,
+        }
+    }
+
+    pub fn into_candidates(self) -> SourceCandidates {
+        self.candidates
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) -> FacePickerAction {
+        let mut action = FacePickerAction::None;
+        let name = self
+            .candidates
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        egui::Window::new("Choose source face")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} faces found in {}, select one or several to average",
+                    self.crops.len(),
+                    name
+                ));
+                ui.horizontal_wrapped(|ui| {
+                    for (idx, crop) in self.crops.iter().enumerate() {
+                        ui.vertical(|ui| {
+                            let image = egui::Image::from_texture(
+                                egui::load::SizedTexture::from_handle(crop),
+                            )
+                            .fit_to_exact_size(CROP_SIZE);
+                            if ui
+                                .add(egui::ImageButton::new(image).selected(self.selected[idx]))
+                                .clicked()
+                            {
+                                self.selected[idx] = !self.selected[idx];
+                            }
+                            ui.label(
+                                egui::RichText::new(format!(
+                                    "#{} {:.2}",
+                                    idx, self.candidates.faces[idx].face.score
+                                ))
+                                .small()
+                                .color(Color32::GRAY),
+                            );
+                        });
+                    }
+                });
+
+                ui.separator();
+                let chosen = self.chosen();
+                ui.horizontal(|ui| {
+                    let label = match chosen.len() {
+                        0 | 1 => "Use face".to_string(),
+                        count => format!("Average {} faces", count),
+                    };
+                    if ui
+                        .add_enabled(!chosen.is_empty(), egui::Button::new(label))
+                        .clicked()
+                    {
+                        action = FacePickerAction::Choose(chosen.clone());
+                    }
+                    if ui.button("Select all").clicked() {
+                        self.selected
+                            .iter_mut()
+                            .for_each(|selected| *selected = true);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = FacePickerAction::Cancel;
+                    }
+                });
+            });
+        action
+    }
+
+    fn chosen(&self) -> Vec<usize> {
+        self.selected
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, selected)| selected.then_some(idx))
+            .collect()
+    }
+}
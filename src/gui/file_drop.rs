@@ -0,0 +1,169 @@
+use std::path::{Path, PathBuf};
+
+use eframe::egui::{self, Align2, Color32, FontId, Id, LayerId, Order, Pos2, Rect, Stroke};
+
+use crate::{
+    cli::{media_kind, MediaKind},
+    model::Embedding,
+};
+
+const HOVER_COLOR: Color32 = Color32::from_rgb(59, 130, 246);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropZone {
+    /// Face image or embedding, sets the source
+    Source,
+    /// Image or video, opens the run dialog with it as target
+    Preview,
+}
+
+impl DropZone {
+    pub fn accepts(&self, path: &Path) -> bool {
+        match self {
+            DropZone::Source => {
+                Embedding::is_embedding_file(path) || media_kind(path) == Some(MediaKind::Image)
+            }
+            DropZone::Preview => media_kind(path).is_some(),
+        }
+    }
+
+    fn hint(&self) -> &'static str {
+        match self {
+            DropZone::Source => "Drop to set source",
+            DropZone::Preview => "Drop to process",
+        }
+    }
+}
+
+/// Screen rects of the drop targets, refreshed every frame
+#[derive(Default)]
+pub struct DropZones {
+    pub source: Option<Rect>,
+    pub preview: Option<Rect>,
+}
+
+impl DropZones {
+    /// Zone under the pointer, by file kind when the platform gives no
+    /// pointer position during drags
+    pub fn zone_at(&self, pos: Option<Pos2>, path: Option<&Path>) -> Option<DropZone> {
+        if let Some(pos) = pos {
+            if self.source.is_some_and(|rect| rect.contains(pos)) {
+                return Some(DropZone::Source);
+            }
+            if self.preview.is_some_and(|rect| rect.contains(pos)) {
+                return Some(DropZone::Preview);
+            }
+            return None;
+        }
+        match path.and_then(media_kind) {
+            Some(MediaKind::Video) => Some(DropZone::Preview),
+            _ => Some(DropZone::Source),
+        }
+    }
+
+    fn rect(&self, zone: DropZone) -> Option<Rect> {
+        match zone {
+            DropZone::Source => self.source,
+            DropZone::Preview => self.preview,
+        }
+    }
+
+    /// Outlines drop targets while files are dragged over the window, the one
+    /// under the pointer is filled
+    pub fn paint_hover(&self, ctx: &egui::Context) {
+        let (hovering, hovered, pos) = ctx.input(|i| {
+            (
+                !i.raw.hovered_files.is_empty(),
+                i.raw
+                    .hovered_files
+                    .first()
+                    .and_then(|file| file.path.clone()),
+                i.pointer.hover_pos(),
+            )
+        });
+        if !hovering {
+            return;
+        }
+
+        let painter = ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("drop_hover")));
+        let target = self.zone_at(pos, hovered.as_deref());
+        for zone in [DropZone::Source, DropZone::Preview] {
+            let Some(rect) = self.rect(zone) else {
+                continue;
+            };
+            let accepted = hovered.as_deref().map_or(true, |path| zone.accepts(path));
+            if target == Some(zone) && accepted {
+                painter.rect_filled(rect, 3., HOVER_COLOR.gamma_multiply(0.25));
+                painter.text(
+                    rect.center(),
+                    Align2::CENTER_CENTER,
+                    zone.hint(),
+                    FontId::proportional(14.),
+                    Color32::WHITE,
+                );
+            }
+            let color = if accepted { HOVER_COLOR } else { Color32::GRAY };
+            painter.rect_stroke(rect, 3., Stroke::new(2., color));
+        }
+        ctx.request_repaint();
+    }
+
+    /// Dropped file with its zone, first file only
+    pub fn take_dropped(&self, ctx: &egui::Context) -> Option<(DropZone, PathBuf)> {
+        let (path, pos) = ctx.input(|i| {
+            (
+                i.raw
+                    .dropped_files
+                    .first()
+                    .and_then(|file| file.path.clone()),
+                i.pointer.hover_pos(),
+            )
+        });
+        let path = path?;
+        let zone = self.zone_at(pos, Some(&path))?;
+        Some((zone, path))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use eframe::egui::{Pos2, Rect, Vec2};
+
+    use super::{DropZone, DropZones};
+
+    #[test]
+    fn picks_zone_by_position_then_kind() {
+        let zones = DropZones {
+            source: Some(Rect::from_min_size(Pos2::ZERO, Vec2::splat(100.))),
+            preview: Some(Rect::from_min_size(Pos2::new(0., 120.), Vec2::splat(100.))),
+        };
+        let clip = Some(Path::new("clip.mp4"));
+
+        assert_eq!(
+            zones.zone_at(Some(Pos2::new(50., 50.)), clip),
+            Some(DropZone::Source)
+        );
+        assert_eq!(
+            zones.zone_at(Some(Pos2::new(50., 150.)), clip),
+            Some(DropZone::Preview)
+        );
+        assert_eq!(zones.zone_at(Some(Pos2::new(300., 300.)), clip), None);
+
+        assert_eq!(zones.zone_at(None, clip), Some(DropZone::Preview));
+        assert_eq!(
+            zones.zone_at(None, Some(Path::new("face.jpg"))),
+            Some(DropZone::Source)
+        );
+    }
+
+    #[test]
+    fn zones_accept_their_file_kinds() {
+        assert!(DropZone::Source.accepts(Path::new("alice.emb")));
+        assert!(DropZone::Source.accepts(Path::new("face.PNG")));
+        assert!(!DropZone::Source.accepts(Path::new("clip.mp4")));
+        assert!(DropZone::Preview.accepts(Path::new("clip.mp4")));
+        assert!(!DropZone::Preview.accepts(Path::new("alice.emb")));
+    }
+}
@@ -0,0 +1,169 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use eframe::egui::{self, TextureHandle, Vec2};
+
+use crate::{cli::IMAGE_EXTENSIONS, image::Image, model::embedding::EMBEDDING_EXTENSION};
+
+use super::proc::GalleryEntry;
+
+const THUMBNAIL_SIZE: Vec2 = Vec2::splat(48.);
+const PROFILE_ICON: egui::ImageSource<'_> = egui::include_image!("../assets/profile.svg");
+
+pub enum GalleryAction {
+    None,
+    Close,
+    Add(Vec<PathBuf>),
+    Select(Option<String>),
+    Rename(String, String),
+    Remove(String),
+}
+
+/// Saved source faces, the selected one is swapped in
+#[derive(Default)]
+pub struct GalleryWindow {
+    thumbnails: HashMap<String, Option<TextureHandle>>,
+    /// Entry id and name being edited
+    renaming: Option<(String, String)>,
+}
+
+impl GalleryWindow {
+    /// `enabled` is false while the source can't change (preview, run)
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        entries: &[GalleryEntry],
+        active: Option<&str>,
+        enabled: bool,
+        thumbnail_of: impl Fn(&str) -> Option<PathBuf>,
+    ) -> GalleryAction {
+        let mut action = GalleryAction::None;
+        let mut open = true;
+
+        egui::Window::new("Gallery")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(280.)
+            .show(ctx, |ui| {
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Add").clicked() {
+                            if let Some(paths) = rfd::FileDialog::new()
+                                .add_filter("Face image or embedding", &source_extensions())
+                                .pick_files()
+                            {
+                                action = GalleryAction::Add(paths);
+                            }
+                        }
+                        if ui
+                            .add_enabled(active.is_some(), egui::Button::new("Clear source"))
+                            .clicked()
+                        {
+                            action = GalleryAction::Select(None);
+                        }
+                    });
+                    ui.separator();
+
+                    if entries.is_empty() {
+                        ui.label("No saved faces, add an image or .emb file");
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(320.)
+                        .show(ui, |ui| {
+                            for entry in entries {
+                                let texture = self.thumbnail(ctx, &entry.id, &thumbnail_of);
+                                let is_active = active == Some(entry.id.as_str());
+                                if let Some(entry_action) =
+                                    self.show_entry(ui, entry, texture, is_active)
+                                {
+                                    action = entry_action;
+                                }
+                            }
+                        });
+                });
+            });
+
+        if let GalleryAction::Remove(id) = &action {
+            self.thumbnails.remove(id);
+        }
+        if !open {
+            action = GalleryAction::Close;
+        }
+        action
+    }
+
+    fn show_entry(
+        &mut self,
+        ui: &mut egui::Ui,
+        entry: &GalleryEntry,
+        texture: Option<TextureHandle>,
+        is_active: bool,
+    ) -> Option<GalleryAction> {
+        let mut action = None;
+        ui.horizontal(|ui| {
+            let image = match &texture {
+                Some(texture) => {
+                    egui::Image::from_texture(egui::load::SizedTexture::from_handle(texture))
+                }
+                None => egui::Image::new(PROFILE_ICON),
+            };
+            let thumbnail =
+                egui::ImageButton::new(image.fit_to_exact_size(THUMBNAIL_SIZE)).selected(is_active);
+            if ui.add(thumbnail).on_hover_text("Use as source").clicked() && !is_active {
+                action = Some(GalleryAction::Select(Some(entry.id.clone())));
+            }
+
+            ui.vertical(|ui| {
+                match self.renaming.as_mut() {
+                    Some((id, name)) if *id == entry.id => {
+                        let response = ui.text_edit_singleline(name);
+                        if response.lost_focus() {
+                            if ui.input(|i| i.key_pressed(egui::Key::Enter)) && !name.is_empty() {
+                                action = Some(GalleryAction::Rename(id.clone(), name.clone()));
+                            }
+                            self.renaming = None;
+                        } else {
+                            response.request_focus();
+                        }
+                    }
+                    _ => {
+                        let label = egui::RichText::new(&entry.name);
+                        ui.label(if is_active { label.strong() } else { label });
+                    }
+                }
+                ui.horizontal(|ui| {
+                    if ui.small_button("Rename").clicked() {
+                        self.renaming = Some((entry.id.clone(), entry.name.clone()));
+                    }
+                    if ui.small_button("Remove").clicked() {
+                        action = Some(GalleryAction::Remove(entry.id.clone()));
+                    }
+                });
+            });
+        });
+        action
+    }
+
+    /// Loads thumbnails once, entries from embedding files have none
+    fn thumbnail(
+        &mut self,
+        ctx: &egui::Context,
+        id: &str,
+        thumbnail_of: &impl Fn(&str) -> Option<PathBuf>,
+    ) -> Option<TextureHandle> {
+        self.thumbnails
+            .entry(id.to_owned())
+            .or_insert_with(|| {
+                let image = Image::from_path(thumbnail_of(id)?, None).ok()?;
+                Some(ctx.load_texture(format!("gallery_{}", id), image, Default::default()))
+            })
+            .clone()
+    }
+}
+
+fn source_extensions() -> Vec<&'static str> {
+    IMAGE_EXTENSIONS
+        .iter()
+        .copied()
+        .chain(std::iter::once(EMBEDDING_EXTENSION))
+        .collect()
+}
@@ -0,0 +1,47 @@
+use std::{path::PathBuf, time::Duration};
+
+use eframe::egui::{self, Color32, Vec2};
+
+use super::proc::LoadState;
+
+const ICON_SIZE: Vec2 = Vec2::splat(64.);
+const LOADING_GIF: egui::ImageSource<'_> = egui::include_image!("../assets/loading.gif");
+const FAIL_ICON: egui::ImageSource<'_> = egui::include_image!("../assets/fail.svg");
+
+pub enum LoadingAction {
+    None,
+    Retry,
+    /// Models folder picked after a failed load
+    UseModelsDir(PathBuf),
+}
+
+/// Full window screen shown until models are loaded
+pub fn show(ui: &mut egui::Ui, state: &LoadState) -> LoadingAction {
+    let mut action = LoadingAction::None;
+    ui.vertical_centered(|ui| {
+        ui.add_space((ui.available_height() * 0.5 - ICON_SIZE.y * 1.5).max(0.));
+        match state {
+            LoadState::Loading | LoadState::Ready => {
+                ui.add(egui::Image::new(LOADING_GIF).fit_to_exact_size(ICON_SIZE));
+                ui.label("Loading models...");
+                // nothing else repaints while the worker loads
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            }
+            LoadState::Failed(err) => {
+                ui.add(egui::Image::new(FAIL_ICON).fit_to_exact_size(ICON_SIZE));
+                ui.heading("Failed to load models");
+                ui.label(egui::RichText::new(err).color(Color32::LIGHT_RED));
+                ui.add_space(8.);
+                if ui.button("Choose models folder").clicked() {
+                    if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                        action = LoadingAction::UseModelsDir(dir);
+                    }
+                }
+                if ui.button("Retry").clicked() {
+                    action = LoadingAction::Retry;
+                }
+            }
+        }
+    });
+    action
+}
@@ -1,12 +1,11 @@
-use std::{
-    sync::{Arc, Mutex},
-    time::{Duration, Instant},
-};
+use std::time::{Duration, Instant};
 
 use eframe::egui::{self, Align2, Color32, Stroke, Style, Vec2};
+use queue::MessageQueue;
 
-use crate::{result::Result, sync::SyncWorker};
+mod queue;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MessageSeverity {
     Info,
     Warning,
@@ -21,80 +20,172 @@ impl MessageSeverity {
             MessageSeverity::Error => Color32::from_rgb(200, 38, 38),
         }
     }
-}
 
-struct Message {
-    content: String,
-    severity: MessageSeverity,
-    requested_at: Instant,
+    /// How long a toast stays, errors need time to be read
+    fn duration(&self) -> Duration {
+        match self {
+            MessageSeverity::Info => Duration::from_secs(3),
+            MessageSeverity::Warning => Duration::from_secs(5),
+            MessageSeverity::Error => Duration::from_secs(10),
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            MessageSeverity::Info => "info",
+            MessageSeverity::Warning => "warning",
+            MessageSeverity::Error => "error",
+        }
+    }
 }
 
+/// Stacked toasts at the top of the window, every message is kept in the
+/// history window
+#[derive(Default)]
 pub struct Messenger {
-    message: Arc<Mutex<Option<Message>>>,
-    duration: Arc<Duration>,
-    worker: SyncWorker,
+    queue: MessageQueue,
+    history_open: bool,
+    /// Warnings and errors sent while the history was closed
+    unseen: usize,
 }
 
 impl Messenger {
-    pub fn new(duration: Duration) -> Self {
-        Self {
-            message: Arc::new(Mutex::new(None)),
-            duration: Arc::new(duration),
-            worker: SyncWorker::new(Some("gui_messgenger_worker".into())),
+    pub fn register_messenger(&mut self, ctx: &egui::Context) {
+        let now = Instant::now();
+        self.queue.expire_at(now);
+        if let Some(next) = self.queue.next_expiry(now) {
+            ctx.request_repaint_after(next);
         }
-    }
 
-    pub fn register_messenger(&mut self, ctx: &egui::Context) -> Result<()> {
-        let ctx = ctx.clone();
-        let msg_opt = Arc::clone(&self.message);
-        let duration = Arc::clone(&self.duration);
-        self.worker.send(move || {
-            let mut open = true;
-            let mut msg_opt = msg_opt
-                .lock()
-                .unwrap_or_else(|err| panic!("Failed to get msg from guard: {}", err));
-            let Some(msg) = msg_opt.as_ref() else { return };
-
-            if msg.requested_at.elapsed() > *duration {
-                open = false;
-            }
-            let severity_color = msg.severity.get_color();
-            let msg_window = egui::Window::new("messege")
-                .open(&mut open)
-                .movable(false)
-                .resizable(false)
-                .title_bar(false)
-                .fade_in(true)
-                .fade_out(true)
-                .anchor(Align2::CENTER_TOP, Vec2::new(0., 5.))
-                .max_width(ctx.used_size().x * 0.75)
-                .frame(
-                    egui::Frame::window(&Style::default())
+        let (mut dismissed, mut hovered) = (None, None);
+        egui::Area::new(egui::Id::new("messenger_toasts"))
+            .anchor(Align2::CENTER_TOP, Vec2::new(0., 5.))
+            .order(egui::Order::Foreground)
+            .interactable(true)
+            .show(ctx, |ui| {
+                ui.set_max_width(ctx.screen_rect().width() * 0.75);
+                for toast in self.queue.toasts().iter().rev() {
+                    let severity_color = toast.severity.get_color();
+                    let frame = egui::Frame::window(&Style::default())
                         .multiply_with_opacity(0.8)
-                        .stroke(Stroke::new(1., severity_color)),
-                )
-                .show(&ctx, |ui| {
-                    ui.label(
-                        egui::RichText::new(msg.content.clone())
-                            .monospace()
-                            .color(severity_color),
-                    )
-                });
-
-            if msg_window.is_none() {
-                *msg_opt = None;
-            }
-        })?;
+                        .stroke(Stroke::new(1., severity_color))
+                        .show(ui, |ui| {
+                            let content = match toast.count {
+                                1 => toast.content.clone(),
+                                count => format!("{} (x{})", toast.content, count),
+                            };
+                            ui.label(
+                                egui::RichText::new(content)
+                                    .monospace()
+                                    .color(severity_color),
+                            )
+                        });
+                    let response = ui
+                        .interact(
+                            frame.response.rect,
+                            ui.id().with(toast.id),
+                            egui::Sense::click(),
+                        )
+                        .on_hover_text("Click to dismiss");
+                    if response.clicked() {
+                        dismissed = Some(toast.id);
+                    } else if response.hovered() {
+                        hovered = Some(toast.id);
+                    }
+                }
+            });
 
-        self.worker.recv()?;
-        Ok(())
+        if let Some(id) = dismissed {
+            self.queue.dismiss(id);
+        }
+        if let Some(id) = hovered {
+            self.queue.hold(id, now);
+        }
     }
 
     pub fn send_message(&mut self, msg: impl Into<String>, severity: Option<MessageSeverity>) {
-        *self.message.lock().unwrap() = Some(Message {
-            content: msg.into(),
-            severity: severity.unwrap_or(MessageSeverity::Info),
-            requested_at: Instant::now(),
-        });
+        let severity = severity.unwrap_or(MessageSeverity::Info);
+        if !self.history_open && severity != MessageSeverity::Info {
+            self.unseen += 1;
+        }
+        self.queue.push(msg.into(), severity);
+    }
+
+    pub fn toggle_history(&mut self) {
+        self.history_open = !self.history_open;
+        self.unseen = 0;
+    }
+
+    /// Label for the button opening the history
+    pub fn history_label(&self) -> String {
+        match self.unseen {
+            0 => "Messages".into(),
+            unseen => format!("Messages ({})", unseen),
+        }
+    }
+
+    pub fn show_history(&mut self, ctx: &egui::Context) {
+        if !self.history_open {
+            return;
+        }
+        let mut open = true;
+        let mut clear = false;
+
+        egui::Window::new("Messages")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(360.)
+            .show(ctx, |ui| {
+                let history = self.queue.history();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!history.is_empty(), egui::Button::new("Copy all"))
+                        .clicked()
+                    {
+                        let lines = history
+                            .iter()
+                            .map(|entry| entry.to_line())
+                            .collect::<Vec<_>>();
+                        ctx.copy_text(lines.join("\n"));
+                    }
+                    if ui
+                        .add_enabled(!history.is_empty(), egui::Button::new("Clear"))
+                        .clicked()
+                    {
+                        clear = true;
+                    }
+                });
+                ui.separator();
+
+                if history.is_empty() {
+                    ui.label("No messages yet");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(320.)
+                    .show(ui, |ui| {
+                        for entry in history.iter().rev() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("Copy").clicked() {
+                                    ctx.copy_text(entry.to_line());
+                                }
+                                ui.add(
+                                    egui::Label::new(
+                                        egui::RichText::new(entry.to_line())
+                                            .monospace()
+                                            .color(entry.severity.get_color()),
+                                    )
+                                    .wrap(),
+                                );
+                            });
+                        }
+                    });
+            });
+
+        if clear {
+            self.queue.clear_history();
+        }
+        if !open {
+            self.history_open = false;
+        }
     }
 }
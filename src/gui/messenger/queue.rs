@@ -0,0 +1,224 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use super::MessageSeverity;
+
+/// Toasts shown at once, older ones stay in the history
+const MAX_TOASTS: usize = 5;
+const MAX_HISTORY: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub id: u64,
+    pub content: String,
+    pub severity: MessageSeverity,
+    /// Times the message was sent while shown
+    pub count: usize,
+    shown_at: Instant,
+}
+
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub content: String,
+    pub severity: MessageSeverity,
+    pub sent_at: SystemTime,
+    pub count: usize,
+}
+
+impl HistoryEntry {
+    /// `[12:04:51] error: content`, time in UTC like the logs
+    pub fn to_line(&self) -> String {
+        let repeated = match self.count {
+            1 => String::new(),
+            count => format!(" (x{})", count),
+        };
+        format!(
+            "[{}] {}: {}{}",
+            format_time(self.sent_at),
+            self.severity.label(),
+            self.content,
+            repeated
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MessageQueue {
+    toasts: Vec<Toast>,
+    history: Vec<HistoryEntry>,
+    next_id: u64,
+}
+
+impl MessageQueue {
+    pub fn push(&mut self, content: String, severity: MessageSeverity) {
+        self.push_at(content, severity, Instant::now(), SystemTime::now());
+    }
+
+    /// Repeats of a shown toast restart its timer instead of stacking
+    pub fn push_at(
+        &mut self,
+        content: String,
+        severity: MessageSeverity,
+        now: Instant,
+        sent_at: SystemTime,
+    ) {
+        if let Some(toast) = self
+            .toasts
+            .iter_mut()
+            .find(|toast| toast.content == content && toast.severity == severity)
+        {
+            toast.count += 1;
+            toast.shown_at = now;
+            if let Some(entry) = self
+                .history
+                .iter_mut()
+                .rev()
+                .find(|entry| entry.content == content && entry.severity == severity)
+            {
+                entry.count += 1;
+                entry.sent_at = sent_at;
+            }
+            return;
+        }
+
+        self.toasts.push(Toast {
+            id: self.next_id,
+            content: content.clone(),
+            severity,
+            count: 1,
+            shown_at: now,
+        });
+        self.next_id += 1;
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+
+        self.history.push(HistoryEntry {
+            content,
+            severity,
+            sent_at,
+            count: 1,
+        });
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+    }
+
+    /// Drops toasts shown longer than their severity duration
+    pub fn expire_at(&mut self, now: Instant) {
+        self.toasts
+            .retain(|toast| now.duration_since(toast.shown_at) < toast.severity.duration());
+    }
+
+    /// Time until the next toast expires, for scheduling a repaint
+    pub fn next_expiry(&self, now: Instant) -> Option<Duration> {
+        self.toasts
+            .iter()
+            .map(|toast| {
+                toast
+                    .severity
+                    .duration()
+                    .saturating_sub(now.duration_since(toast.shown_at))
+            })
+            .min()
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.toasts.retain(|toast| toast.id != id);
+    }
+
+    /// Keeps a hovered toast from expiring
+    pub fn hold(&mut self, id: u64, now: Instant) {
+        if let Some(toast) = self.toasts.iter_mut().find(|toast| toast.id == id) {
+            toast.shown_at = now;
+        }
+    }
+
+    pub fn toasts(&self) -> &[Toast] {
+        &self.toasts
+    }
+
+    /// Oldest first
+    pub fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+}
+
+/// `HH:MM:SS` in UTC
+fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default()
+        % 86400;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    use super::{MessageQueue, MessageSeverity};
+
+    #[test]
+    fn dedups_and_expires_by_severity() {
+        let mut queue = MessageQueue::default();
+        let start = Instant::now();
+        let sent_at = SystemTime::now();
+
+        queue.push_at("saved".into(), MessageSeverity::Info, start, sent_at);
+        queue.push_at("failed".into(), MessageSeverity::Error, start, sent_at);
+        queue.push_at(
+            "failed".into(),
+            MessageSeverity::Error,
+            start + Duration::from_secs(1),
+            sent_at,
+        );
+        assert_eq!(queue.toasts().len(), 2);
+        assert_eq!(queue.toasts()[1].count, 2);
+        assert_eq!(queue.history().len(), 2);
+        assert_eq!(queue.history()[1].count, 2);
+
+        // info is gone before the error
+        queue.expire_at(start + MessageSeverity::Info.duration());
+        assert_eq!(queue.toasts().len(), 1);
+        assert_eq!(queue.toasts()[0].content, "failed");
+
+        let id = queue.toasts()[0].id;
+        queue.dismiss(id);
+        assert!(queue.toasts().is_empty());
+        assert_eq!(queue.history().len(), 2);
+
+        // same message after it expired is a new entry
+        queue.push_at("saved".into(), MessageSeverity::Info, start, sent_at);
+        assert_eq!(queue.history().len(), 3);
+    }
+
+    #[test]
+    fn caps_stacked_toasts() {
+        let mut queue = MessageQueue::default();
+        for idx in 0..8 {
+            queue.push(format!("message {}", idx), MessageSeverity::Warning);
+        }
+        assert_eq!(queue.toasts().len(), 5);
+        assert_eq!(queue.toasts()[0].content, "message 3");
+        assert_eq!(queue.history().len(), 8);
+    }
+
+    #[test]
+    fn formats_history_lines() {
+        let mut queue = MessageQueue::default();
+        let sent_at = UNIX_EPOCH + Duration::from_secs(86400 + 3600 * 13 + 60 * 4 + 5);
+        let now = Instant::now();
+        queue.push_at("no face".into(), MessageSeverity::Error, now, sent_at);
+        queue.push_at("no face".into(), MessageSeverity::Error, now, sent_at);
+
+        assert_eq!(
+            queue.history()[0].to_line(),
+            "[13:04:05] error: no face (x2)"
+        );
+    }
+}
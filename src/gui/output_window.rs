@@ -0,0 +1,105 @@
+use eframe::egui::{self, Color32, Pos2, Rect, Sense, TextureHandle, ViewportCommand};
+
+use super::view::fit_rect;
+
+pub enum OutputWindowAction {
+    None,
+    Close,
+}
+
+/// Borderless window showing only the output frame, for screen capture.
+/// Dragging moves it, double click or F11 toggles fullscreen
+#[derive(Default)]
+pub struct OutputWindow {
+    fullscreen: bool,
+}
+
+impl OutputWindow {
+    fn id() -> egui::ViewportId {
+        egui::ViewportId::from_hash_of("output_window")
+    }
+
+    /// `background` fills the space around the frame, a chroma key color lets
+    /// capture software cut it out
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        output: &TextureHandle,
+        background: Color32,
+    ) -> OutputWindowAction {
+        let builder = egui::ViewportBuilder::default()
+            .with_title("noface output")
+            .with_decorations(false)
+            .with_inner_size([640., 360.])
+            .with_min_inner_size([160., 90.]);
+
+        ctx.show_viewport_immediate(Self::id(), builder, |ctx, class| {
+            if class == egui::ViewportClass::Embedded {
+                // backend without multiple windows, shown inside the main one
+                let mut open = true;
+                egui::Window::new("Output")
+                    .open(&mut open)
+                    .default_size([320., 180.])
+                    .frame(egui::Frame::window(&ctx.style()).fill(background))
+                    .show(ctx, |ui| {
+                        let rect = ui.available_rect_before_wrap();
+                        paint_output(ui, rect, output);
+                    });
+                return if open {
+                    OutputWindowAction::None
+                } else {
+                    OutputWindowAction::Close
+                };
+            }
+
+            egui::CentralPanel::default()
+                .frame(egui::Frame::none().fill(background))
+                .show(ctx, |ui| {
+                    let rect = ui.max_rect();
+                    let response = ui.interact(rect, ui.id().with("output_drag"), Sense::drag());
+                    if response.drag_started() && !self.fullscreen {
+                        ctx.send_viewport_cmd(ViewportCommand::StartDrag);
+                    }
+                    paint_output(ui, rect, output);
+                });
+            self.handle_input(ctx)
+        })
+    }
+
+    fn handle_input(&mut self, ctx: &egui::Context) -> OutputWindowAction {
+        let (toggle, escape, close) = ctx.input(|i| {
+            (
+                i.key_pressed(egui::Key::F11)
+                    || i.pointer
+                        .button_double_clicked(egui::PointerButton::Primary),
+                i.key_pressed(egui::Key::Escape),
+                i.viewport().close_requested(),
+            )
+        });
+        if close {
+            return OutputWindowAction::Close;
+        }
+        if escape && !self.fullscreen {
+            return OutputWindowAction::Close;
+        }
+        if toggle || escape {
+            self.fullscreen = !self.fullscreen;
+            ctx.send_viewport_cmd(ViewportCommand::Fullscreen(self.fullscreen));
+        }
+        OutputWindowAction::None
+    }
+}
+
+/// Output frame fitted in `rect` keeping its aspect ratio
+fn paint_output(ui: &mut egui::Ui, rect: Rect, output: &TextureHandle) {
+    let size = output.size_vec2();
+    if size.x <= 0. || size.y <= 0. {
+        return;
+    }
+    ui.painter().image(
+        output.id(),
+        fit_rect(rect, size),
+        Rect::from_min_max(Pos2::ZERO, Pos2::new(1., 1.)),
+        Color32::WHITE,
+    );
+}
@@ -0,0 +1,84 @@
+use eframe::egui::{self, Align2, Color32, FontId, Rect, Stroke, Vec2};
+
+use super::proc::{FrameInfo, TargetRules, TrackRule};
+
+const BOX_COLOR: Color32 = Color32::from_rgb(34, 197, 94);
+const KEYPOINT_COLOR: Color32 = Color32::from_rgb(239, 68, 68);
+
+/// Paints detections of `info` over the frame drawn in `rect`
+pub fn paint(ui: &egui::Ui, rect: Rect, info: &FrameInfo, rules: &TargetRules) {
+    let painter = ui.painter().with_clip_rect(rect);
+    let (width, height) = info.size;
+
+    if width > 0 && height > 0 {
+        let scale = Vec2::new(rect.width() / width as f32, rect.height() / height as f32);
+        let to_screen = |x: f32, y: f32| rect.min + Vec2::new(x * scale.x, y * scale.y);
+
+        for (face, bbox) in info.faces.iter().zip(info.face_rects(rect)) {
+            // swapped faces stand out
+            let stroke_width = match rules.rule(face.track_id) {
+                TrackRule::Skip => 1.,
+                TrackRule::Swap | TrackRule::Source(_) => 2.,
+            };
+            painter.rect_stroke(bbox, 0., Stroke::new(stroke_width, BOX_COLOR));
+
+            for [x, y] in face.keypoints.iter() {
+                painter.circle_filled(to_screen(*x, *y), 2., KEYPOINT_COLOR);
+            }
+
+            let label = match face.track_id {
+                Some(id) => format!("#{} {:.2}", id, face.score),
+                None => format!("{:.2}", face.score),
+            };
+            painter.text(
+                bbox.left_top() - Vec2::new(0., 2.),
+                Align2::LEFT_BOTTOM,
+                label,
+                FontId::monospace(11.),
+                BOX_COLOR,
+            );
+        }
+    }
+
+    let hud = format!(
+        "{:.1} fps  {} ms  {} faces",
+        info.fps,
+        info.latency.as_millis(),
+        info.faces.len()
+    );
+    let galley = painter.layout_no_wrap(hud, FontId::monospace(11.), Color32::WHITE);
+    let hud_rect = Rect::from_min_size(rect.min + Vec2::splat(4.), galley.size() + Vec2::splat(6.));
+    painter.rect_filled(hud_rect, 2., Color32::from_black_alpha(160));
+    painter.galley(hud_rect.min + Vec2::splat(3.), galley, Color32::WHITE);
+}
+
+const SWAP_COLOR: Color32 = Color32::from_rgb(34, 197, 94);
+const SOURCE_COLOR: Color32 = Color32::from_rgb(59, 130, 246);
+
+/// Outlines faces by how they are swapped, `source_name` labels gallery sources
+pub fn paint_targets(
+    ui: &egui::Ui,
+    rect: Rect,
+    info: &FrameInfo,
+    rules: &TargetRules,
+    source_name: impl Fn(&str) -> Option<String>,
+) {
+    let painter = ui.painter().with_clip_rect(rect);
+    for (face, bbox) in info.faces.iter().zip(info.face_rects(rect)) {
+        let (color, label) = match rules.rule(face.track_id) {
+            TrackRule::Swap => (SWAP_COLOR, None),
+            TrackRule::Skip => (Color32::GRAY, Some("skip".to_string())),
+            TrackRule::Source(id) => (SOURCE_COLOR, Some(source_name(&id).unwrap_or(id))),
+        };
+        painter.rect_stroke(bbox, 2., Stroke::new(1.5, color));
+        if let Some(label) = label {
+            painter.text(
+                bbox.left_bottom() + Vec2::new(0., 2.),
+                Align2::LEFT_TOP,
+                label,
+                FontId::proportional(11.),
+                color,
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use eframe::egui::{self, Align2, Color32, FontId, Pos2, Sense, Stroke, Vec2};
+
+use crate::model::{Stage, TimingHistory};
+
+const PLOT_SIZE: Vec2 = Vec2::new(280., 90.);
+
+fn stage_color(stage: Stage) -> Color32 {
+    match stage {
+        Stage::Detect => Color32::from_rgb(59, 130, 246),
+        Stage::Align => Color32::from_rgb(168, 85, 247),
+        Stage::Embed => Color32::from_rgb(236, 72, 153),
+        Stage::Swap => Color32::from_rgb(34, 197, 94),
+        Stage::Paste => Color32::from_rgb(234, 179, 8),
+        Stage::Display => Color32::from_rgb(239, 68, 68),
+    }
+}
+
+/// Per stage timings of the latest frames with a rolling plot, drawn over the
+/// bottom right of the window
+pub fn show(ctx: &egui::Context, history: &TimingHistory) {
+    egui::Area::new(egui::Id::new("perf_hud"))
+        .anchor(Align2::RIGHT_BOTTOM, Vec2::new(-8., -8.))
+        .order(egui::Order::Foreground)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::window(ui.style())
+                .multiply_with_opacity(0.85)
+                .show(ui, |ui| {
+                    if history.is_empty() {
+                        ui.label("Waiting for frames");
+                        return;
+                    }
+                    show_table(ui, history);
+                    ui.add_space(4.);
+                    paint_plot(ui, history);
+                });
+        });
+}
+
+fn show_table(ui: &mut egui::Ui, history: &TimingHistory) {
+    let ms = |duration: std::time::Duration| format!("{:.1}", duration.as_secs_f32() * 1000.);
+    egui::Grid::new("perf_hud_table")
+        .num_columns(4)
+        .spacing(Vec2::new(12., 2.))
+        .show(ui, |ui| {
+            for header in ["ms", "now", "avg", "p95"] {
+                ui.monospace(header);
+            }
+            ui.end_row();
+            for stage in Stage::ALL {
+                let stats = history.stats(stage);
+                ui.label(
+                    egui::RichText::new(stage.name())
+                        .monospace()
+                        .color(stage_color(stage)),
+                );
+                ui.monospace(ms(stats.current));
+                ui.monospace(ms(stats.avg));
+                ui.monospace(ms(stats.p95));
+                ui.end_row();
+            }
+        });
+}
+
+/// One line per stage, scaled to the slowest sample in the window
+fn paint_plot(ui: &mut egui::Ui, history: &TimingHistory) {
+    let (rect, _) = ui.allocate_exact_size(PLOT_SIZE, Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2., Color32::from_black_alpha(120));
+
+    let max_ms = Stage::ALL
+        .into_iter()
+        .flat_map(|stage| history.samples(stage))
+        .max()
+        .map(|duration| duration.as_secs_f32() * 1000.)
+        .unwrap_or_default()
+        .max(1.);
+    let len = history.samples(Stage::Detect).count();
+    let step = rect.width() / (len.max(2) - 1) as f32;
+
+    for stage in Stage::ALL {
+        let points = history
+            .samples(stage)
+            .enumerate()
+            .map(|(idx, duration)| {
+                let ms = duration.as_secs_f32() * 1000.;
+                Pos2::new(
+                    rect.left() + idx as f32 * step,
+                    rect.bottom() - ms / max_ms * rect.height(),
+                )
+            })
+            .collect::<Vec<_>>();
+        painter.add(egui::Shape::line(
+            points,
+            Stroke::new(1., stage_color(stage)),
+        ));
+    }
+
+    painter.text(
+        rect.left_top() + Vec2::splat(3.),
+        Align2::LEFT_TOP,
+        format!("{:.1} ms", max_ms),
+        FontId::monospace(10.),
+        Color32::WHITE,
+    );
+}
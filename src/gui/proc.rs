@@ -1,11 +1,35 @@
-use crate::{cv::CV, image::Image, model::Model, sync::ResultWorker, Error, Result};
-use std::sync::{Arc, Mutex, RwLock};
+use crate::{
+    cv::CV,
+    image::Image,
+    model::{
+        data::{Tracker, VectorizedTensor},
+        timing, Embedding, EmbeddingNorm, EmbeddingSource, Model, SourceFace, Stage, StageTimings,
+        Tensor, TimingHistory, VECTORIZATION_DIM,
+    },
+    setting::{Config, GuiConfig, ModelConfig},
+    sync::ResultWorker,
+    Error, Result,
+};
+use std::{
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
 
+pub use capture::RecordingReport;
+pub use frame::FrameInfo;
+pub use gallery::{Gallery, GalleryEntry};
+pub use models::{LoadState, ModelSlot};
+pub use run::{RunProgress, RunReport, RunTarget};
+pub use source::SourceCandidates;
+pub use targets::{DefaultRule, TargetRules, TrackRule};
+
+mod capture;
 mod frame;
+mod gallery;
+mod models;
+mod run;
 mod source;
-
-// 30 FPS -> 33ms
-const FRAME_DELAY: u64 = 33;
+mod targets;
 
 const LOADING_GIF: eframe::egui::ImageSource<'_> =
     eframe::egui::include_image!("../assets/loading.gif");
@@ -23,22 +47,49 @@ pub enum ProcStatus {
 
 pub struct Processor {
     pub status: Arc<RwLock<ProcStatus>>,
-    pub model: Arc<Mutex<Model>>,
+    pub model: Arc<ModelSlot>,
+    load: Arc<RwLock<LoadState>>,
     pub source: Arc<RwLock<source::Source>>,
+    /// Saved sources, the active entry is the one loaded in `source`
+    pub gallery: Arc<RwLock<Gallery>>,
+    candidates: Arc<RwLock<Option<SourceCandidates>>>,
     pub frame: Arc<RwLock<frame::Frame>>,
+    /// Target frame before swap, for comparison views
+    pub original: Arc<RwLock<frame::Frame>>,
+    pub info: Arc<RwLock<FrameInfo>>,
+    /// Stage timings of the latest frames, for the performance hud
+    pub perf: Arc<RwLock<TimingHistory>>,
+    /// Preview recording and pending snapshot
+    capture: Arc<Mutex<capture::Capture>>,
+    /// Which tracked faces get swapped and with what
+    pub targets: Arc<RwLock<TargetRules>>,
+    pub run: Arc<RwLock<RunProgress>>,
+    /// Camera and frame rate used by preview and runs
+    gui: Arc<RwLock<GuiConfig>>,
     worker: ResultWorker<Result<()>>,
 }
 
 impl Processor {
-    #[tracing::instrument(name = "Initializing Gui Processor", skip(config), err)]
-    pub fn new(config: &crate::setting::Config) -> Result<Self> {
-        Ok(Self {
+    /// Models are loaded separately by `load_model`
+    #[tracing::instrument(name = "Initializing Gui Processor", skip(config))]
+    pub fn new(config: &crate::setting::Config) -> Self {
+        Self {
             status: Arc::new(RwLock::new(ProcStatus::NotInitialized)),
-            model: Arc::new(Mutex::new(Model::new(&config.model)?)),
+            model: Arc::new(ModelSlot::default()),
+            load: Arc::new(RwLock::new(LoadState::default())),
             source: Arc::new(RwLock::new(source::Source::default())),
+            gallery: Arc::new(RwLock::new(Gallery::default())),
+            candidates: Arc::new(RwLock::new(None)),
             frame: Arc::new(RwLock::new(frame::Frame::default())),
+            original: Arc::new(RwLock::new(frame::Frame::default())),
+            info: Arc::new(RwLock::new(FrameInfo::default())),
+            perf: Arc::new(RwLock::new(TimingHistory::default())),
+            capture: Arc::new(Mutex::new(capture::Capture::default())),
+            targets: Arc::new(RwLock::new(TargetRules::default())),
+            run: Arc::new(RwLock::new(RunProgress::default())),
+            gui: Arc::new(RwLock::new(config.gui.clone())),
             worker: ResultWorker::new("proc_worker"),
-        })
+        }
     }
 
     pub fn register(&mut self, ctx: &eframe::egui::Context) -> Result<()> {
@@ -52,7 +103,13 @@ impl Processor {
             self.frame
                 .write()
                 .map_err(Error::as_guard_error)?
-                .register(ctx)
+                .register(ctx, "processor_frame")
+        }
+        {
+            self.original
+                .write()
+                .map_err(Error::as_guard_error)?
+                .register(ctx, "processor_original")
         }
         Ok(())
     }
@@ -69,6 +126,93 @@ impl Processor {
         Ok(())
     }
 
+    /// Applies settings that don't need a model reload, call `load_model`
+    /// too when `ModelConfig::needs_reload`
+    pub fn apply_config(&self, config: &Config) -> Result<()> {
+        {
+            *self.gui.write().map_err(Error::as_guard_error)? = config.gui.clone();
+        }
+        // a later load reads them from the config
+        if self.model.is_loaded() {
+            self.model.lock()?.set_options(&config.model);
+        }
+        Ok(())
+    }
+
+    pub fn is_model_loaded(&self) -> bool {
+        self.model.is_loaded()
+    }
+
+    pub fn get_load_state(&self) -> LoadState {
+        match self.load.read() {
+            Ok(load) => load.clone(),
+            Err(_) => LoadState::default(),
+        }
+    }
+
+    /// Builds new sessions in the worker, a loaded model keeps serving until
+    /// they are ready. The first load also restores the active gallery source
+    pub fn load_model(&mut self, config: ModelConfig) -> Result<()> {
+        let loaded = self.model.is_loaded();
+        let previous = self.get_status();
+        if loaded {
+            self.set_status(ProcStatus::Processing)?;
+        }
+        {
+            *self.load.write().map_err(Error::as_guard_error)? = LoadState::Loading;
+        }
+        let (status, load, model, source, gallery) = (
+            Arc::clone(&self.status),
+            Arc::clone(&self.load),
+            Arc::clone(&self.model),
+            Arc::clone(&self.source),
+            Arc::clone(&self.gallery),
+        );
+
+        self.worker.send(move || {
+            let new_model = match Model::new(&config) {
+                Ok(new_model) => new_model,
+                Err(err) => {
+                    {
+                        *load.write().map_err(Error::as_guard_error)? =
+                            LoadState::Failed(err.to_string());
+                    }
+                    // the gui shows the error screen while nothing is loaded
+                    if !loaded {
+                        return Ok(());
+                    }
+                    *status.write().map_err(Error::as_guard_error)? = previous;
+                    return Err(err);
+                }
+            };
+            model.replace(new_model)?;
+            {
+                *load.write().map_err(Error::as_guard_error)? = LoadState::Ready;
+            }
+
+            if loaded {
+                *status.write().map_err(Error::as_guard_error)? = previous;
+                return Ok(());
+            }
+            let active = {
+                gallery
+                    .read()
+                    .map_err(Error::as_guard_error)?
+                    .active()
+                    .map(|entry| entry.id.clone())
+            };
+            if let Some(id) = active {
+                load_gallery_source(&model, &gallery, &source, &id)?;
+                *status.write().map_err(Error::as_guard_error)? = ProcStatus::Idle;
+            }
+            Ok(())
+        })
+    }
+
+    fn get_gui_config(&self) -> Result<GuiConfig> {
+        Ok(self.gui.read().map_err(Error::as_guard_error)?.clone())
+    }
+
     pub fn get_source_img(&self) -> eframe::egui::Image {
         use eframe::egui;
         let status = self.get_status();
@@ -79,12 +223,27 @@ impl Processor {
                 let Ok(src) = src_binding.as_deref() else {
                     return egui::Image::new(PROFILE_ICON);
                 };
+                // embedding sources have no face image
+                if src.texture.size() == [0, 0] {
+                    return egui::Image::new(PROFILE_ICON);
+                }
                 egui::Image::from_texture(egui::load::SizedTexture::from_handle(&src.texture))
             }
             _ => egui::Image::new(PROFILE_ICON),
         }
     }
 
+    pub fn get_original_frame(&self) -> Result<eframe::egui::TextureHandle> {
+        Ok(self.original.read().map_err(Error::as_guard_error)?.clone())
+    }
+
+    pub fn get_frame_info(&self) -> FrameInfo {
+        match self.info.read() {
+            Ok(info) => info.clone(),
+            Err(_) => FrameInfo::default(),
+        }
+    }
+
     pub fn get_frame(&self) -> Result<eframe::egui::TextureHandle> {
         Ok(self
             .frame
@@ -96,27 +255,45 @@ impl Processor {
             .clone())
     }
 
+    /// Adds the face in `path` (image or `.emb`) to the gallery and makes it
+    /// the active source
     pub fn set_source_with_path(&mut self, path: std::path::PathBuf) -> Result<()> {
         self.set_status(ProcStatus::Processing)?;
-        let (stataus, source, model) = (
+        let (stataus, source, gallery, candidates, model) = (
             Arc::clone(&self.status),
             Arc::clone(&self.source),
+            Arc::clone(&self.gallery),
+            Arc::clone(&self.candidates),
             Arc::clone(&self.model),
         );
 
         self.worker.send(move || {
-            let img = Image::from_path(path, None)?;
-            let (tensor, vec_tensor) = {
-                model
-                    .lock()
-                    .map_err(Error::as_guard_error)?
-                    .vectorize_tensor(img.into())?
-            };
-            {
-                source
-                    .write()
-                    .map_err(Error::as_guard_error)?
-                    .set_from_tensor(tensor, vec_tensor);
+            if Embedding::is_embedding_file(&path) {
+                let embedding = Embedding::load(&path)?;
+                let vec_tensor = { model.lock()?.source_from_embedding(&embedding)? };
+                {
+                    let mut gallery = gallery.write().map_err(Error::as_guard_error)?;
+                    let entry = gallery.add(source_name(&path), &embedding, None)?;
+                    gallery.set_active(Some(&entry.id))?;
+                }
+                {
+                    source
+                        .write()
+                        .map_err(Error::as_guard_error)?
+                        .set_from_embedding(vec_tensor);
+                }
+                *stataus.write().map_err(Error::as_guard_error)? = ProcStatus::Idle;
+                return Ok(());
+            }
+
+            let img = Image::from_path(path.clone(), None)?;
+            let faces = { model.lock()?.embed_faces(img.into())? };
+            if faces.len() > 1 {
+                // the gui asks which face to use
+                *candidates.write().map_err(Error::as_guard_error)? =
+                    Some(SourceCandidates { path, faces });
+            } else {
+                add_source(&model, &gallery, &source, &path, faces)?;
             }
             {
                 *stataus.write().map_err(Error::as_guard_error)? = ProcStatus::Idle;
@@ -125,18 +302,122 @@ impl Processor {
         })
     }
 
+    /// Faces of a group photo source waiting to be chosen, returned once
+    pub fn take_source_candidates(&self) -> Option<SourceCandidates> {
+        self.candidates.write().ok()?.take()
+    }
+
+    /// Stores the chosen candidates as the source, several are averaged
+    pub fn choose_source_faces(
+        &mut self,
+        candidates: SourceCandidates,
+        indices: Vec<usize>,
+    ) -> Result<()> {
+        let faces = indices
+            .into_iter()
+            .filter_map(|idx| candidates.faces.get(idx).cloned())
+            .collect::<Vec<_>>();
+        self.set_status(ProcStatus::Processing)?;
+        let (status, source, gallery, model) = (
+            Arc::clone(&self.status),
+            Arc::clone(&self.source),
+            Arc::clone(&self.gallery),
+            Arc::clone(&self.model),
+        );
+
+        self.worker.send(move || {
+            add_source(&model, &gallery, &source, &candidates.path, faces)?;
+            *status.write().map_err(Error::as_guard_error)? = ProcStatus::Idle;
+            Ok(())
+        })
+    }
+
+    /// Loads a gallery entry as the source, `None` clears it
+    pub fn select_gallery_source(&mut self, id: Option<String>) -> Result<()> {
+        self.set_status(ProcStatus::Processing)?;
+        let (status, source, gallery, model) = (
+            Arc::clone(&self.status),
+            Arc::clone(&self.source),
+            Arc::clone(&self.gallery),
+            Arc::clone(&self.model),
+        );
+
+        self.worker.send(move || {
+            let Some(id) = id else {
+                {
+                    gallery
+                        .write()
+                        .map_err(Error::as_guard_error)?
+                        .set_active(None)?;
+                }
+                {
+                    source.write().map_err(Error::as_guard_error)?.clear();
+                }
+                *status.write().map_err(Error::as_guard_error)? = ProcStatus::Idle;
+                return Ok(());
+            };
+
+            load_gallery_source(&model, &gallery, &source, &id)?;
+            *status.write().map_err(Error::as_guard_error)? = ProcStatus::Idle;
+            Ok(())
+        })
+    }
+
+    pub fn get_gallery_entries(&self) -> (Vec<GalleryEntry>, Option<String>) {
+        match self.gallery.read() {
+            Ok(gallery) => (
+                gallery.entries().to_vec(),
+                gallery.active().map(|entry| entry.id.clone()),
+            ),
+            Err(_) => (vec![], None),
+        }
+    }
+
+    pub fn rename_gallery_source(&self, id: &str, name: String) -> Result<()> {
+        self.gallery
+            .write()
+            .map_err(Error::as_guard_error)?
+            .rename(id, name)
+    }
+
+    /// Removes the entry and its files, clears the source when it was active
+    pub fn remove_gallery_source(&self, id: &str) -> Result<()> {
+        let was_active = {
+            let mut gallery = self.gallery.write().map_err(Error::as_guard_error)?;
+            let was_active = gallery.active().is_some_and(|entry| entry.id == id);
+            gallery.remove(id)?;
+            was_active
+        };
+        if was_active {
+            self.source.write().map_err(Error::as_guard_error)?.clear();
+        }
+        Ok(())
+    }
+
     pub fn run_preview(&mut self) -> Result<()> {
-        use std::time::{Duration, Instant};
+        use std::time::Instant;
+        let gui = self.get_gui_config()?;
         self.set_status(ProcStatus::Previewing)?;
-        let (status, frame, source, model) = (
+        self.reset_frame_state()?;
+        {
+            self.capture.lock().map_err(Error::as_guard_error)?.reset();
+        }
+        let (status, frame, original, info, perf, capture, targets, source, model) = (
             Arc::clone(&self.status),
             Arc::clone(&self.frame),
+            Arc::clone(&self.original),
+            Arc::clone(&self.info),
+            Arc::clone(&self.perf),
+            Arc::clone(&self.capture),
+            Arc::clone(&self.targets),
             Arc::clone(&self.source),
             Arc::clone(&self.model),
         );
 
         self.worker.send(move || {
-            let mut cv = CV::new()?;
+            let mut cv = CV::new(gui.camera)?;
+            let delay = frame_delay(gui.fps);
+            let mut tracker = Tracker::default();
             loop {
                 {
                     if *status.read().map_err(Error::as_guard_error)? != ProcStatus::Previewing {
@@ -144,39 +425,272 @@ impl Processor {
                             .write()
                             .map_err(Error::as_guard_error)?
                             .set(crate::image::Image::default(), Default::default());
+                        original
+                            .write()
+                            .map_err(Error::as_guard_error)?
+                            .set(crate::image::Image::default(), Default::default());
+                        // finishes a recording the gui didn't stop
+                        capture
+                            .lock()
+                            .map_err(Error::as_guard_error)?
+                            .stop_recording();
                         break;
                     }
                 }
                 let start_inst = Instant::now();
-                let mat = cv.get_frame()?;
+                let tar: Tensor = cv.get_frame()?.into();
+                {
+                    original
+                        .write()
+                        .map_err(Error::as_guard_error)?
+                        .set(tar.clone(), Default::default());
+                }
 
                 // Processing Starts
                 let src = { source.read().map_err(Error::as_guard_error)?.data.clone() };
-                let data = {
-                    model
-                        .lock()
-                        .map_err(Error::as_guard_error)?
-                        .run(mat.into(), src.into())?
-                };
+                let (data, mut timings) =
+                    process_frame(&model, &info, &targets, &mut tracker, tar, src)?;
                 // Processing Ends
 
+                let data = Image::from(data);
                 {
-                    frame
-                        .write()
-                        .map_err(Error::as_guard_error)?
-                        .set(data, Default::default());
+                    let mut frame = frame.write().map_err(Error::as_guard_error)?;
+                    timing::measure(&mut timings, Stage::Display, || {
+                        frame.set(data.clone(), Default::default())
+                    });
+                }
+                {
+                    perf.write().map_err(Error::as_guard_error)?.push(timings);
+                }
+                {
+                    capture.lock().map_err(Error::as_guard_error)?.push(data)?;
                 }
 
                 // let duration_since = Instant::now().duration_since(start_inst);
                 let duration_since = start_inst.elapsed();
-                if Duration::from_millis(FRAME_DELAY) > duration_since {
-                    std::thread::sleep(Duration::from_millis(FRAME_DELAY) - duration_since)
+                if delay > duration_since {
+                    std::thread::sleep(delay - duration_since)
                 }
             }
             Ok(())
         })
     }
 
+    /// Swaps target into output, camera records until cancelled
+    pub fn run(&mut self, target: RunTarget, output: std::path::PathBuf) -> Result<()> {
+        let gui = self.get_gui_config()?;
+        self.set_status(ProcStatus::Running)?;
+        {
+            *self.run.write().map_err(Error::as_guard_error)? = RunProgress::default();
+        }
+        self.reset_frame_state()?;
+        let (status, frame, original, info, perf, targets, source, model, progress) = (
+            Arc::clone(&self.status),
+            Arc::clone(&self.frame),
+            Arc::clone(&self.original),
+            Arc::clone(&self.info),
+            Arc::clone(&self.perf),
+            Arc::clone(&self.targets),
+            Arc::clone(&self.source),
+            Arc::clone(&self.model),
+            Arc::clone(&self.run),
+        );
+
+        self.worker.send(move || {
+            let src = { source.read().map_err(Error::as_guard_error)?.data.clone() };
+            let frames = run::Runner {
+                model: &model,
+                src,
+                frame: &frame,
+                original: &original,
+                info: &info,
+                perf: &perf,
+                targets: &targets,
+                tracker: Tracker::default(),
+                progress: &progress,
+                frame_delay: frame_delay(gui.fps),
+                camera: gui.camera,
+            }
+            .run(&target, &output)?;
+
+            {
+                let mut progress = progress.write().map_err(Error::as_guard_error)?;
+                progress.report = Some(RunReport {
+                    frames,
+                    output,
+                    // camera only ends by cancel
+                    cancelled: progress.cancelled && target != RunTarget::Camera,
+                });
+            }
+            {
+                frame
+                    .write()
+                    .map_err(Error::as_guard_error)?
+                    .set(Image::default(), Default::default());
+            }
+            {
+                original
+                    .write()
+                    .map_err(Error::as_guard_error)?
+                    .set(Image::default(), Default::default());
+            }
+            *status.write().map_err(Error::as_guard_error)? = ProcStatus::Idle;
+            Ok(())
+        })
+    }
+
+    /// Has the preview worker save its next frame as png in the captures
+    /// folder, the outcome comes from `take_snapshot_result`
+    pub fn request_snapshot(&self) -> Result<()> {
+        let dir = self.get_gui_config()?.output.captures_dir()?;
+        std::fs::create_dir_all(&dir).map_err(Error::as_unknown_error)?;
+
+        let path = capture::capture_path(&dir, "snapshot", "png");
+        self.capture
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .request_snapshot(path)
+    }
+
+    /// Saved snapshot path or its error, returned once
+    pub fn take_snapshot_result(&self) -> Option<Result<std::path::PathBuf>> {
+        self.capture.lock().ok()?.take_saved()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.capture
+            .lock()
+            .map(|capture| capture.is_recording())
+            .unwrap_or(false)
+    }
+
+    /// Records preview frames to an mp4 in the captures folder until stopped
+    pub fn start_recording(&self) -> Result<std::path::PathBuf> {
+        let gui = self.get_gui_config()?;
+        let dir = gui.output.captures_dir()?;
+        std::fs::create_dir_all(&dir).map_err(Error::as_unknown_error)?;
+
+        // measured rate keeps playback speed when frames take longer than the target
+        let fps = match self.get_frame_info().fps {
+            fps if fps > 0. => fps as f64,
+            _ => 1. / frame_delay(gui.fps).as_secs_f64(),
+        };
+        let path = capture::capture_path(&dir, "recording", "mp4");
+        self.capture
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .start_recording(path.clone(), fps)?;
+        Ok(path)
+    }
+
+    pub fn stop_recording(&self) -> Result<Option<RecordingReport>> {
+        Ok(self
+            .capture
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .stop_recording())
+    }
+
+    /// Track ids restart with every preview or run, so do their rules
+    fn reset_frame_state(&self) -> Result<()> {
+        {
+            *self.info.write().map_err(Error::as_guard_error)? = FrameInfo::default();
+        }
+        {
+            *self.perf.write().map_err(Error::as_guard_error)? = TimingHistory::default();
+        }
+        self.targets.write().map_err(Error::as_guard_error)?.reset();
+        Ok(())
+    }
+
+    pub fn get_perf(&self) -> TimingHistory {
+        match self.perf.read() {
+            Ok(perf) => perf.clone(),
+            Err(_) => TimingHistory::default(),
+        }
+    }
+
+    pub fn get_target_rules(&self) -> TargetRules {
+        match self.targets.read() {
+            Ok(targets) => targets.clone(),
+            Err(_) => TargetRules::default(),
+        }
+    }
+
+    /// Toggles swapping of the tracked face
+    pub fn toggle_target(&self, track_id: usize) -> Result<()> {
+        self.targets
+            .write()
+            .map_err(Error::as_guard_error)?
+            .toggle(track_id);
+        Ok(())
+    }
+
+    /// Rule of faces nobody picked a rule for, kept across previews and runs
+    pub fn set_default_target_rule(&self, rule: DefaultRule) -> Result<()> {
+        self.targets
+            .write()
+            .map_err(Error::as_guard_error)?
+            .set_default_rule(rule);
+        Ok(())
+    }
+
+    pub fn set_target_rule(&self, track_id: usize, rule: TrackRule) -> Result<()> {
+        self.targets
+            .write()
+            .map_err(Error::as_guard_error)?
+            .set(track_id, rule);
+        Ok(())
+    }
+
+    /// Swaps the tracked face with a gallery entry
+    pub fn assign_target_source(&self, track_id: usize, id: String) -> Result<()> {
+        let loaded = {
+            self.targets
+                .read()
+                .map_err(Error::as_guard_error)?
+                .has_source(&id)
+        };
+        if !loaded {
+            let embedding = {
+                self.gallery
+                    .read()
+                    .map_err(Error::as_guard_error)?
+                    .embedding(&id)?
+            };
+            self.targets
+                .write()
+                .map_err(Error::as_guard_error)?
+                .queue_source(id.clone(), embedding);
+        }
+        self.set_target_rule(track_id, TrackRule::Source(id))
+    }
+
+    pub fn get_run_progress(&self) -> RunProgress {
+        match self.run.read() {
+            Ok(progress) => progress.clone(),
+            Err(_) => RunProgress::default(),
+        }
+    }
+
+    pub fn toggle_pause(&self) -> Result<()> {
+        let mut progress = self.run.write().map_err(Error::as_guard_error)?;
+        progress.paused = !progress.paused;
+        Ok(())
+    }
+
+    /// Ends current run after the frame in progress, status goes back to
+    /// idle once the output is finalized
+    pub fn cancel_run(&self) -> Result<()> {
+        self.run.write().map_err(Error::as_guard_error)?.cancelled = true;
+        Ok(())
+    }
+
+    /// Report of the last finished run, returned once
+    pub fn take_run_report(&self) -> Option<RunReport> {
+        self.run.write().ok()?.report.take()
+    }
+
     pub fn stop(&mut self) -> Result<()> {
         self.set_status(ProcStatus::Idle)
     }
@@ -204,8 +718,164 @@ impl Processor {
     }
 }
 
+fn source_name(path: &std::path::Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "face".into())
+}
+
+/// Saves `faces` (averaged when several) to the gallery and makes them the
+/// active source, the first face is the thumbnail
+fn add_source(
+    model: &ModelSlot,
+    gallery: &RwLock<Gallery>,
+    source: &RwLock<source::Source>,
+    path: &std::path::Path,
+    faces: Vec<SourceFace>,
+) -> Result<()> {
+    // raw vectors are stored so entries survive swap model changes
+    let mut embedding = Embedding::new(
+        model.lock()?.recognizer_name(),
+        VECTORIZATION_DIM,
+        EmbeddingNorm::None,
+    );
+    let file = EmbeddingSource::from_path(path)?;
+    for face in faces.iter() {
+        embedding.push(face.raw.clone(), file.clone())?;
+    }
+    let Some(first) = faces.into_iter().next() else {
+        return Err(Error::InvalidModelIOError("No face selected".into()));
+    };
+
+    let vec_tensor = { model.lock()?.source_from_embedding(&embedding)? };
+    {
+        let mut gallery = gallery.write().map_err(Error::as_guard_error)?;
+        let entry = gallery.add(
+            source_name(path),
+            &embedding,
+            Some(&Image::from(first.tensor.clone())),
+        )?;
+        gallery.set_active(Some(&entry.id))?;
+    }
+    source
+        .write()
+        .map_err(Error::as_guard_error)?
+        .set_from_tensor(first.tensor, vec_tensor);
+    Ok(())
+}
+
+/// Makes gallery entry `id` the active source
+fn load_gallery_source(
+    model: &ModelSlot,
+    gallery: &RwLock<Gallery>,
+    source: &RwLock<source::Source>,
+    id: &str,
+) -> Result<()> {
+    let (embedding, thumbnail) = {
+        let gallery = gallery.read().map_err(Error::as_guard_error)?;
+        (gallery.embedding(id)?, gallery.thumbnail(id))
+    };
+    let vec_tensor = { model.lock()?.source_from_embedding(&embedding)? };
+    let thumbnail = thumbnail
+        .map(|path| Image::from_path(path, None))
+        .transpose()?;
+    {
+        gallery
+            .write()
+            .map_err(Error::as_guard_error)?
+            .set_active(Some(id))?;
+    }
+    let mut source = source.write().map_err(Error::as_guard_error)?;
+    match thumbnail {
+        Some(thumbnail) => source.set_from_tensor(thumbnail, vec_tensor),
+        None => source.set_from_embedding(vec_tensor),
+    }
+    Ok(())
+}
+
+/// Camera frame interval, 30 fps -> 33ms
+fn frame_delay(fps: u32) -> Duration {
+    Duration::from_millis(1000 / fps.max(1) as u64)
+}
+
+/// Swaps faces as chosen by `targets` and publishes detections to `info`, returns the frame's stage timings
+fn process_frame(
+    model: &ModelSlot,
+    info: &RwLock<FrameInfo>,
+    targets: &RwLock<TargetRules>,
+    tracker: &mut Tracker,
+    mut tar: Tensor,
+    src: VectorizedTensor,
+) -> Result<(Tensor, StageTimings)> {
+    let start_inst = std::time::Instant::now();
+    let (_, _, height, width) = tar.dim();
+
+    prepare_sources(model, targets)?;
+    let mut faces = { model.lock()?.detect(tar.clone())? };
+    // track ids are needed before swapping to look up rules
+    tracker.update(&mut faces);
+
+    let swaps = {
+        let targets = targets.read().map_err(Error::as_guard_error)?;
+        faces
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, face)| Some((idx, targets.source_for(face.track_id, &src)?)))
+            .collect::<Vec<_>>()
+    };
+    let timings = {
+        let mut model = model.lock()?;
+        for (idx, vector) in swaps {
+            model.swap_face(&mut tar, &faces[idx], vector)?;
+        }
+        model.take_timings()
+    };
+
+    info.write().map_err(Error::as_guard_error)?.update(
+        faces,
+        (width, height),
+        start_inst.elapsed(),
+    );
+    Ok((tar, timings))
+}
+
+/// Prepares vectors of gallery sources assigned since the last frame, a
+/// mismatched embedding leaves its tracks unswapped
+fn prepare_sources(model: &ModelSlot, targets: &RwLock<TargetRules>) -> Result<()> {
+    let pending = {
+        targets
+            .write()
+            .map_err(Error::as_guard_error)?
+            .take_pending()
+    };
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let prepared = {
+        let model = model.lock()?;
+        pending
+            .into_iter()
+            .filter_map(
+                |(id, embedding)| match model.source_from_embedding(&embedding) {
+                    Ok(vector) => Some((id, vector)),
+                    Err(err) => {
+                        tracing::warn!("Skipping gallery source {}: {}", id, err);
+                        None
+                    }
+                },
+            )
+            .collect::<Vec<_>>()
+    };
+    let mut targets = targets.write().map_err(Error::as_guard_error)?;
+    for (id, vector) in prepared {
+        targets.insert_source(id, vector);
+    }
+    Ok(())
+}
+
 impl Drop for Processor {
     fn drop(&mut self) {
+        let _ = self.cancel_run();
         let _ = self.set_status(ProcStatus::Idle);
     }
 }
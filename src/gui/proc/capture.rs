@@ -0,0 +1,192 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{cv::VideoWriter, image::Image, Error, Result};
+
+/// Preview recording, the writer is created with the first frame size
+struct Recording {
+    path: PathBuf,
+    fps: f64,
+    writer: Option<VideoWriter>,
+    frames: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecordingReport {
+    pub path: PathBuf,
+    pub frames: usize,
+}
+
+/// Running preview recording and requested snapshot
+#[derive(Default)]
+pub struct Capture {
+    recording: Option<Recording>,
+    /// Saved from the next pushed frame
+    snapshot: Option<PathBuf>,
+    /// Outcome of the last snapshot, returned once
+    saved: Option<Result<PathBuf>>,
+}
+
+impl Capture {
+    /// Appends `image` to the recording and saves a requested snapshot
+    pub fn push(&mut self, image: Image) -> Result<()> {
+        if let Some(path) = self.snapshot.take() {
+            // a failed snapshot doesn't stop the preview
+            self.saved = Some(image.save(&path).map(|_| path).map_err(Error::ImageError));
+        }
+        if let Some(recording) = self.recording.as_mut() {
+            if recording.writer.is_none() {
+                let (width, height) = image.dimensions();
+                recording.writer = Some(VideoWriter::create(
+                    &recording.path,
+                    recording.fps,
+                    (width as i32, height as i32),
+                )?);
+            }
+            if let Some(writer) = recording.writer.as_mut() {
+                writer.write_image(&image)?;
+                recording.frames += 1;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn request_snapshot(&mut self, path: PathBuf) -> Result<()> {
+        if self.snapshot.is_some() {
+            return Err(Error::UnknownError("Snapshot already pending".into()));
+        }
+        self.snapshot = Some(path);
+        Ok(())
+    }
+
+    pub fn take_saved(&mut self) -> Option<Result<PathBuf>> {
+        self.saved.take()
+    }
+
+    pub fn start_recording(&mut self, path: PathBuf, fps: f64) -> Result<()> {
+        if self.recording.is_some() {
+            return Err(Error::UnknownError("Already recording".into()));
+        }
+        self.recording = Some(Recording {
+            path,
+            fps,
+            writer: None,
+            frames: 0,
+        });
+        Ok(())
+    }
+
+    /// Finishes the file, `None` when not recording
+    pub fn stop_recording(&mut self) -> Option<RecordingReport> {
+        let recording = self.recording.take()?;
+        // dropping the writer releases the file
+        drop(recording.writer);
+        Some(RecordingReport {
+            path: recording.path,
+            frames: recording.frames,
+        })
+    }
+
+    /// Drops a pending snapshot and ends any recording
+    pub fn reset(&mut self) -> Option<RecordingReport> {
+        self.snapshot = None;
+        self.stop_recording()
+    }
+}
+
+/// `<prefix>_<unix seconds>.<ext>` in `dir`, numbered when taken
+pub fn capture_path(dir: &Path, prefix: &str, ext: &str) -> PathBuf {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default();
+    let stem = format!("{}_{}", prefix, secs);
+
+    let mut path = dir.join(format!("{}.{}", stem, ext));
+    let mut count = 1;
+    while path.exists() {
+        path = dir.join(format!("{}_{}.{}", stem, count, ext));
+        count += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use crate::image::Image;
+
+    use super::{capture_path, Capture};
+
+    #[test]
+    fn numbers_taken_capture_names() {
+        let dir = std::env::temp_dir().join(format!("noface_capture_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = capture_path(&dir, "snapshot", "png");
+        assert!(first
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("snapshot_"));
+        std::fs::write(&first, b"").unwrap();
+
+        let second = capture_path(&dir, "snapshot", "png");
+        assert_ne!(first, second);
+        assert_eq!(second.extension().unwrap(), "png");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn reset_ends_recording_and_snapshot() {
+        let mut capture = Capture::default();
+        capture.push(Image::default()).unwrap();
+
+        capture.request_snapshot("snapshot.png".into()).unwrap();
+        capture.start_recording("clip.mp4".into(), 30.).unwrap();
+        assert!(capture.start_recording("other.mp4".into(), 30.).is_err());
+
+        let report = capture.reset().unwrap();
+        assert_eq!(report.frames, 0);
+        assert!(!capture.is_recording());
+        assert!(capture.request_snapshot("snapshot.png".into()).is_ok());
+    }
+
+    #[test]
+    fn saves_snapshot_from_next_frame() {
+        let dir = std::env::temp_dir().join(format!("noface_snapshot_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("snapshot.png");
+
+        let mut capture = Capture::default();
+        capture.request_snapshot(path.clone()).unwrap();
+        assert!(capture.request_snapshot(path.clone()).is_err());
+        assert!(capture.take_saved().is_none());
+
+        capture
+            .push(Image::from(image::RgbImage::new(4, 2)))
+            .unwrap();
+        assert_eq!(capture.take_saved().unwrap().unwrap(), path);
+        assert!(capture.take_saved().is_none());
+        assert_eq!(image::image_dimensions(&path).unwrap(), (4, 2));
+
+        // bad path is reported without failing the frame
+        capture
+            .request_snapshot(dir.join("missing/snapshot.png"))
+            .unwrap();
+        assert!(capture
+            .push(Image::from(image::RgbImage::new(4, 2)))
+            .is_ok());
+        assert!(capture.take_saved().unwrap().is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,5 +1,70 @@
+use std::time::{Duration, Instant};
+
+use eframe::egui::{Pos2, Rect, Vec2};
+
+use crate::model::data::Face;
+
 pub struct Frame(pub eframe::egui::TextureHandle);
 
+/// Detection result of the last processed frame, for the debug overlay
+#[derive(Debug, Clone, Default)]
+pub struct FrameInfo {
+    pub faces: Vec<Face>,
+    /// (width, height) of the processed frame
+    pub size: (usize, usize),
+    /// Detection and swap time
+    pub latency: Duration,
+    /// Smoothed frame rate
+    pub fps: f32,
+    last_at: Option<Instant>,
+}
+
+impl FrameInfo {
+    pub fn update(&mut self, faces: Vec<Face>, size: (usize, usize), latency: Duration) {
+        let now = Instant::now();
+        if let Some(last_at) = self.last_at {
+            let fps = 1. / now.duration_since(last_at).as_secs_f32().max(f32::EPSILON);
+            self.fps = if self.fps == 0. {
+                fps
+            } else {
+                self.fps * 0.9 + fps * 0.1
+            };
+        }
+        self.last_at = Some(now);
+        self.faces = faces;
+        self.size = size;
+        self.latency = latency;
+    }
+
+    /// Face boxes in display coordinates of a frame drawn in `rect`
+    pub fn face_rects(&self, rect: Rect) -> Vec<Rect> {
+        let (width, height) = self.size;
+        if width == 0 || height == 0 {
+            return vec![];
+        }
+        let scale = Vec2::new(rect.width() / width as f32, rect.height() / height as f32);
+        let to_screen = |x: f32, y: f32| rect.min + Vec2::new(x * scale.x, y * scale.y);
+
+        self.faces
+            .iter()
+            .map(|face| {
+                let (x1, y1, x2, y2) = face.bbox;
+                Rect::from_min_max(to_screen(x1, y1), to_screen(x2, y2))
+            })
+            .collect()
+    }
+
+    /// Index of the face under `pos`, smallest box wins when they overlap
+    pub fn face_at(&self, rect: Rect, pos: Pos2) -> Option<usize> {
+        self.face_rects(rect)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, face)| face.contains(pos))
+            .min_by(|(_, a), (_, b)| a.area().total_cmp(&b.area()))
+            .map(|(idx, _)| idx)
+    }
+}
+
 impl Default for Frame {
     fn default() -> Self {
         Self(eframe::egui::Context::default().load_texture(
@@ -11,12 +76,8 @@ impl Default for Frame {
 }
 
 impl Frame {
-    pub fn register(&mut self, ctx: &eframe::egui::Context) {
-        self.0 = ctx.load_texture(
-            "prcoessor_frame",
-            crate::image::Image::default(),
-            Default::default(),
-        )
+    pub fn register(&mut self, ctx: &eframe::egui::Context, name: &str) {
+        self.0 = ctx.load_texture(name, crate::image::Image::default(), Default::default())
     }
 }
 
@@ -33,3 +94,42 @@ impl std::ops::DerefMut for Frame {
         &mut self.0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use eframe::egui::{Pos2, Rect, Vec2};
+
+    use crate::model::data::{Face, KeyPoints};
+
+    use super::FrameInfo;
+
+    fn face(bbox: (f32, f32, f32, f32)) -> Face {
+        Face {
+            score: 0.9,
+            keypoints: KeyPoints([[0.; 2]; 5]),
+            bbox,
+            track_id: None,
+        }
+    }
+
+    #[test]
+    fn maps_faces_to_display_coordinates() {
+        let info = FrameInfo {
+            faces: vec![face((0., 0., 400., 400.)), face((100., 100., 200., 200.))],
+            size: (800, 400),
+            ..Default::default()
+        };
+        // drawn at half size, offset by the view margin
+        let rect = Rect::from_min_size(Pos2::new(10., 20.), Vec2::new(400., 200.));
+
+        let rects = info.face_rects(rect);
+        assert_eq!(
+            rects[1],
+            Rect::from_min_max(Pos2::new(60., 70.), Pos2::new(110., 120.))
+        );
+
+        assert_eq!(info.face_at(rect, Pos2::new(80., 90.)), Some(1));
+        assert_eq!(info.face_at(rect, Pos2::new(30., 30.)), Some(0));
+        assert_eq!(info.face_at(rect, Pos2::new(300., 30.)), None);
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    image::Image,
+    model::{embedding::EMBEDDING_EXTENSION, Embedding},
+    Error, Result,
+};
+
+/// Relative to the working directory like `models`
+pub const GALLERY_DIR: &str = "data/gallery";
+const INDEX_FILE: &str = "index.json";
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct GalleryEntry {
+    /// File stem of the stored `.emb` and thumbnail
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Default, Debug)]
+struct GalleryIndex {
+    entries: Vec<GalleryEntry>,
+    active: Option<String>,
+}
+
+/// Source faces persisted as `<id>.emb` with a `<id>.png` thumbnail, names and
+/// order kept in `index.json`
+#[derive(Debug)]
+pub struct Gallery {
+    dir: PathBuf,
+    index: GalleryIndex,
+}
+
+impl Default for Gallery {
+    fn default() -> Self {
+        Self::open(PathBuf::from(GALLERY_DIR))
+    }
+}
+
+impl Gallery {
+    /// Starts empty when the index is missing or unreadable
+    pub fn open(dir: PathBuf) -> Self {
+        let index = match fs::read_to_string(dir.join(INDEX_FILE)) {
+            Ok(index) => serde_json::from_str(&index).unwrap_or_else(|err| {
+                tracing::warn!("Ignoring invalid gallery index: {}", err);
+                GalleryIndex::default()
+            }),
+            Err(err) => {
+                if err.kind() != ErrorKind::NotFound {
+                    tracing::warn!("Failed reading gallery index: {}", err);
+                }
+                GalleryIndex::default()
+            }
+        };
+        Self { dir, index }
+    }
+
+    pub fn entries(&self) -> &[GalleryEntry] {
+        &self.index.entries
+    }
+
+    pub fn get(&self, id: &str) -> Option<&GalleryEntry> {
+        self.index.entries.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn active(&self) -> Option<&GalleryEntry> {
+        self.get(self.index.active.as_deref()?)
+    }
+
+    pub fn set_active(&mut self, id: Option<&str>) -> Result<()> {
+        if let Some(id) = id {
+            self.entry_index(id)?;
+        }
+        self.index.active = id.map(str::to_owned);
+        self.save()
+    }
+
+    /// Stores `embedding` under a new id, `thumbnail` is usually the aligned face
+    pub fn add(
+        &mut self,
+        name: impl Into<String>,
+        embedding: &Embedding,
+        thumbnail: Option<&Image>,
+    ) -> Result<GalleryEntry> {
+        fs::create_dir_all(&self.dir).map_err(Error::as_unknown_error)?;
+
+        let entry = GalleryEntry {
+            id: self.next_id(),
+            name: name.into(),
+        };
+        embedding.save(&self.embedding_path(&entry.id))?;
+        if let Some(thumbnail) = thumbnail {
+            thumbnail
+                .save(self.thumbnail_path(&entry.id))
+                .map_err(Error::ImageError)?;
+        }
+
+        self.index.entries.push(entry.clone());
+        self.save()?;
+        Ok(entry)
+    }
+
+    pub fn rename(&mut self, id: &str, name: impl Into<String>) -> Result<()> {
+        let idx = self.entry_index(id)?;
+        self.index.entries[idx].name = name.into();
+        self.save()
+    }
+
+    pub fn remove(&mut self, id: &str) -> Result<()> {
+        let idx = self.entry_index(id)?;
+        self.index.entries.remove(idx);
+        if self.index.active.as_deref() == Some(id) {
+            self.index.active = None;
+        }
+        self.save()?;
+
+        for path in [self.embedding_path(id), self.thumbnail_path(id)] {
+            if let Err(err) = fs::remove_file(&path) {
+                if err.kind() != ErrorKind::NotFound {
+                    return Err(Error::as_unknown_error(err));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn embedding(&self, id: &str) -> Result<Embedding> {
+        self.entry_index(id)?;
+        Embedding::load(&self.embedding_path(id))
+    }
+
+    /// Path of the thumbnail, `None` for entries added from embedding files
+    pub fn thumbnail(&self, id: &str) -> Option<PathBuf> {
+        let path = self.thumbnail_path(id);
+        path.is_file().then_some(path)
+    }
+
+    fn entry_index(&self, id: &str) -> Result<usize> {
+        self.index
+            .entries
+            .iter()
+            .position(|entry| entry.id == id)
+            .ok_or_else(|| Error::UnknownError(format!("Unknown gallery entry: {}", id).into()))
+    }
+
+    fn next_id(&self) -> String {
+        (1..)
+            .map(|n| format!("face_{}", n))
+            .find(|id| self.get(id).is_none() && !self.embedding_path(id).exists())
+            .unwrap_or_default()
+    }
+
+    fn embedding_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", id, EMBEDDING_EXTENSION))
+    }
+
+    fn thumbnail_path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.png", id))
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::create_dir_all(&self.dir).map_err(Error::as_unknown_error)?;
+        let index = serde_json::to_string_pretty(&self.index).map_err(Error::as_unknown_error)?;
+        write_atomic(&self.dir.join(INDEX_FILE), index.as_bytes())
+    }
+}
+
+/// Writes through a temp file so a crash can't leave a truncated index
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, bytes).map_err(Error::as_unknown_error)?;
+    fs::rename(&tmp, path).map_err(Error::as_unknown_error)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::{
+        data::VectorizedTensor, Embedding, EmbeddingNorm, EmbeddingSource, VECTORIZATION_DIM,
+    };
+
+    use super::Gallery;
+
+    fn embedding() -> Embedding {
+        let mut embedding = Embedding::new("test", VECTORIZATION_DIM, EmbeddingNorm::None);
+        embedding
+            .push(
+                VectorizedTensor::from(ndarray::Array::from_shape_fn(
+                    (1, VECTORIZATION_DIM),
+                    |(_, i)| if i == 0 { 1. } else { 0. },
+                )),
+                EmbeddingSource {
+                    file: "face.jpg".into(),
+                    sha256: String::new(),
+                },
+            )
+            .unwrap();
+        embedding
+    }
+
+    #[test]
+    fn persists_entries_across_reopen() {
+        let dir = std::env::temp_dir().join(format!("noface_gallery_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut gallery = Gallery::open(dir.clone());
+        let alice = gallery.add("alice", &embedding(), None).unwrap();
+        let bob = gallery.add("bob", &embedding(), None).unwrap();
+        assert_ne!(alice.id, bob.id);
+
+        gallery.set_active(Some(&bob.id)).unwrap();
+        gallery.rename(&alice.id, "Alice").unwrap();
+
+        let mut gallery = Gallery::open(dir.clone());
+        assert_eq!(gallery.entries().len(), 2);
+        assert_eq!(gallery.entries()[0].name, "Alice");
+        assert_eq!(gallery.active(), Some(&bob));
+        assert_eq!(gallery.embedding(&alice.id).unwrap().meta.count, 1);
+        assert!(gallery.thumbnail(&alice.id).is_none());
+
+        gallery.remove(&bob.id).unwrap();
+        assert_eq!(gallery.active(), None);
+        assert!(gallery.embedding(&bob.id).is_err());
+        assert_eq!(Gallery::open(dir.clone()).entries().len(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    ops::{Deref, DerefMut},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, MutexGuard,
+    },
+};
+
+use crate::{model::Model, Error, Result};
+
+/// Outcome of the latest model load
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LoadState {
+    #[default]
+    Loading,
+    Ready,
+    /// Error message, shown with a way to pick another models folder
+    Failed(String),
+}
+
+/// Model built after the window opens, empty until a load succeeds
+#[derive(Default)]
+pub struct ModelSlot {
+    model: Mutex<Option<Model>>,
+    /// Read by the gui every frame without waiting on inference
+    loaded: AtomicBool,
+}
+
+impl ModelSlot {
+    /// Errors while no model is loaded
+    pub fn lock(&self) -> Result<ModelGuard<'_>> {
+        let guard = self.model.lock().map_err(Error::as_guard_error)?;
+        if guard.is_none() {
+            return Err(Error::UnknownError("Models are not loaded".into()));
+        }
+        Ok(ModelGuard(guard))
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.loaded.load(Ordering::SeqCst)
+    }
+
+    pub fn replace(&self, model: Model) -> Result<()> {
+        *self.model.lock().map_err(Error::as_guard_error)? = Some(model);
+        self.loaded.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Locked model, only handed out by `ModelSlot::lock` once loaded
+pub struct ModelGuard<'a>(MutexGuard<'a, Option<Model>>);
+
+impl Deref for ModelGuard<'_> {
+    type Target = Model;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("Model checked on lock")
+    }
+}
+
+impl DerefMut for ModelGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.0.as_mut().expect("Model checked on lock")
+    }
+}
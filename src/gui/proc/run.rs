@@ -0,0 +1,334 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cli::{media_kind, MediaKind},
+    cv::{VideoReader, VideoWriter, CV},
+    image::{Animation, Image, ImageSequence},
+    model::{
+        data::{Tracker, VectorizedTensor},
+        timing, Stage, Tensor, TimingHistory,
+    },
+    setting::OutputConfig,
+    Error, Result,
+};
+
+use super::{
+    frame::{Frame, FrameInfo},
+    models::ModelSlot,
+    process_frame,
+    targets::TargetRules,
+};
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum RunTarget {
+    #[default]
+    Camera,
+    /// Image, animated image, video or frame directory or pattern
+    File(PathBuf),
+}
+
+impl RunTarget {
+    /// File name offered by the output dialog, still images use the
+    /// configured output format and frame sequences a directory
+    pub fn default_output(&self, config: &OutputConfig) -> String {
+        let Self::File(path) = self else {
+            return "camera_swapped.mp4".into();
+        };
+        if self.is_sequence() {
+            let dir = if path.is_dir() {
+                Some(path.as_path())
+            } else {
+                path.parent()
+            };
+            let name = dir
+                .and_then(|dir| dir.file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| "frames".into());
+            return format!("{}_swapped", name);
+        }
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "output".into());
+
+        let ext = match media_kind(path) {
+            Some(MediaKind::Video) => "mp4",
+            // animations are written as gif only
+            _ if Animation::is_animated(path).unwrap_or(false) => "gif",
+            _ => config.image_format.extension(),
+        };
+        format!("{}_swapped.{}", stem, ext)
+    }
+
+    /// Frames are written into the output directory one file each
+    pub fn is_sequence(&self) -> bool {
+        matches!(self, Self::File(path) if ImageSequence::is_sequence(path))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RunReport {
+    pub frames: usize,
+    pub output: PathBuf,
+    pub cancelled: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct RunProgress {
+    pub done: usize,
+    /// Unknown for camera
+    pub total: Option<usize>,
+    pub paused: bool,
+    pub cancelled: bool,
+    /// Set when the run ends, taken by the gui for the completion message
+    pub report: Option<RunReport>,
+}
+
+impl RunProgress {
+    pub fn fraction(&self) -> Option<f32> {
+        self.total
+            .filter(|total| *total > 0)
+            .map(|total| (self.done as f32 / total as f32).min(1.))
+    }
+}
+
+pub(super) struct Runner<'a> {
+    pub model: &'a ModelSlot,
+    pub src: VectorizedTensor,
+    pub frame: &'a RwLock<Frame>,
+    pub original: &'a RwLock<Frame>,
+    pub info: &'a RwLock<FrameInfo>,
+    pub perf: &'a RwLock<TimingHistory>,
+    pub targets: &'a RwLock<TargetRules>,
+    pub tracker: Tracker,
+    pub progress: &'a RwLock<RunProgress>,
+    pub frame_delay: Duration,
+    pub camera: i32,
+}
+
+impl Runner<'_> {
+    /// Processed frame count, stops early when cancelled
+    pub fn run(&mut self, target: &RunTarget, output: &Path) -> Result<usize> {
+        match target {
+            RunTarget::Camera => self.run_camera(output),
+            RunTarget::File(path) if target.is_sequence() => self.run_sequence(path, output),
+            RunTarget::File(path) => match media_kind(path) {
+                Some(MediaKind::Video) => self.run_video(path, output),
+                Some(MediaKind::Image) => self.run_image(path, output),
+                None => Err(Error::UnknownError(
+                    format!("Unsupported target: {}", path.display()).into(),
+                )),
+            },
+        }
+    }
+
+    fn set_total(&self, total: Option<usize>) -> Result<()> {
+        self.progress.write().map_err(Error::as_guard_error)?.total = total;
+        Ok(())
+    }
+
+    fn is_cancelled(&self) -> Result<bool> {
+        Ok(self
+            .progress
+            .read()
+            .map_err(Error::as_guard_error)?
+            .cancelled)
+    }
+
+    /// Swaps one frame and shows it, `None` when cancelled
+    fn step(&mut self, tar: Tensor) -> Result<Option<Image>> {
+        loop {
+            let (paused, cancelled) = {
+                let progress = self.progress.read().map_err(Error::as_guard_error)?;
+                (progress.paused, progress.cancelled)
+            };
+            if cancelled {
+                return Ok(None);
+            }
+            if !paused {
+                break;
+            }
+            std::thread::sleep(self.frame_delay);
+        }
+
+        {
+            self.original
+                .write()
+                .map_err(Error::as_guard_error)?
+                .set(tar.clone(), Default::default());
+        }
+        let (output, mut timings) = process_frame(
+            self.model,
+            self.info,
+            self.targets,
+            &mut self.tracker,
+            tar,
+            self.src.clone(),
+        )?;
+        let output = Image::from(output);
+        {
+            let mut frame = self.frame.write().map_err(Error::as_guard_error)?;
+            timing::measure(&mut timings, Stage::Display, || {
+                frame.set(output.clone(), Default::default())
+            });
+        }
+        {
+            self.perf
+                .write()
+                .map_err(Error::as_guard_error)?
+                .push(timings);
+        }
+        self.progress.write().map_err(Error::as_guard_error)?.done += 1;
+        Ok(Some(output))
+    }
+
+    fn run_image(&mut self, path: &Path, output: &Path) -> Result<usize> {
+        if Animation::is_animated(path)? {
+            let animation = Animation::from_path(path)?;
+            self.set_total(Some(animation.frames.len()))?;
+
+            let mut frames = 0;
+            let animation = animation.try_map(|img| {
+                Ok(match self.step(img.clone().into())? {
+                    Some(swapped) => {
+                        frames += 1;
+                        swapped
+                    }
+                    None => img,
+                })
+            })?;
+            if !self.is_cancelled()? {
+                animation.save(output)?;
+            }
+            return Ok(frames);
+        }
+
+        self.set_total(Some(1))?;
+        let Some(swapped) = self.step(Image::from_path(path.to_path_buf(), None)?.into())? else {
+            return Ok(0);
+        };
+        swapped.save(output).map_err(Error::ImageError)?;
+        Ok(1)
+    }
+
+    fn run_sequence(&mut self, path: &Path, output: &Path) -> Result<usize> {
+        let sequence = ImageSequence::from_path(path.to_path_buf())?;
+        sequence.ensure_output_dir(output)?;
+        self.set_total(Some(sequence.len()))?;
+
+        let mut frames = 0;
+        for frame in sequence.frames() {
+            let frame = frame?;
+            let Some(swapped) = self.step(frame.image.clone().into())? else {
+                break;
+            };
+            ImageSequence::write_frame(&frame, &swapped, output)?;
+            frames += 1;
+        }
+        Ok(frames)
+    }
+
+    fn run_video(&mut self, path: &Path, output: &Path) -> Result<usize> {
+        let mut reader = VideoReader::open(path)?;
+        let mut writer = VideoWriter::create(output, reader.fps, reader.size)?;
+        self.set_total((reader.frame_count > 0).then_some(reader.frame_count))?;
+
+        let mut frames = 0;
+        while let Some(mat) = reader.next_frame()? {
+            let Some(swapped) = self.step(mat.into())? else {
+                break;
+            };
+            writer.write_image(&swapped)?;
+            frames += 1;
+        }
+        Ok(frames)
+    }
+
+    /// Records until cancelled
+    fn run_camera(&mut self, output: &Path) -> Result<usize> {
+        let mut cv = CV::new(self.camera)?;
+        let mut writer = None;
+        self.set_total(None)?;
+
+        let mut frames = 0;
+        loop {
+            let start_inst = Instant::now();
+            let Some(swapped) = self.step(cv.get_frame()?.into())? else {
+                break;
+            };
+
+            if writer.is_none() {
+                let (width, height) = swapped.dimensions();
+                writer = Some(VideoWriter::create(
+                    output,
+                    1. / self.frame_delay.as_secs_f64(),
+                    (width as i32, height as i32),
+                )?);
+            }
+            if let Some(writer) = writer.as_mut() {
+                writer.write_image(&swapped)?;
+            }
+            frames += 1;
+
+            let duration_since = start_inst.elapsed();
+            if self.frame_delay > duration_since {
+                std::thread::sleep(self.frame_delay - duration_since)
+            }
+        }
+        Ok(frames)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::setting::{ImageOutputFormat, OutputConfig};
+
+    use super::{RunProgress, RunTarget};
+
+    #[test]
+    fn suggests_output_by_target_kind() {
+        let config = OutputConfig::default();
+        assert_eq!(
+            RunTarget::Camera.default_output(&config),
+            "camera_swapped.mp4"
+        );
+        assert_eq!(
+            RunTarget::File("clips/intro.mov".into()).default_output(&config),
+            "intro_swapped.mp4"
+        );
+        assert_eq!(
+            RunTarget::File("photo.jpg".into()).default_output(&config),
+            "photo_swapped.png"
+        );
+
+        let jpeg = OutputConfig {
+            image_format: ImageOutputFormat::Jpeg,
+            ..Default::default()
+        };
+        assert_eq!(
+            RunTarget::File("photo.png".into()).default_output(&jpeg),
+            "photo_swapped.jpg"
+        );
+        assert_eq!(
+            RunTarget::File("renders/shot_%04d.png".into()).default_output(&config),
+            "renders_swapped"
+        );
+    }
+
+    #[test]
+    fn progress_fraction_is_bounded() {
+        let mut progress = RunProgress::default();
+        assert_eq!(progress.fraction(), None);
+
+        progress.total = Some(4);
+        progress.done = 1;
+        assert_eq!(progress.fraction(), Some(0.25));
+
+        progress.done = 6;
+        assert_eq!(progress.fraction(), Some(1.));
+    }
+}
@@ -1,8 +1,16 @@
+use std::path::PathBuf;
+
 use crate::{
     image::Image,
-    model::{data::VectorizedTensor, Tensor},
+    model::{data::VectorizedTensor, SourceFace},
 };
 
+/// Faces detected in a source image with several, waiting for the user's choice
+pub struct SourceCandidates {
+    pub path: PathBuf,
+    pub faces: Vec<SourceFace>,
+}
+
 pub struct Source {
     pub data: VectorizedTensor,
     pub texture: eframe::egui::TextureHandle,
@@ -26,8 +34,22 @@ impl Source {
         self.texture = ctx.load_texture("processor_source", Image::default(), Default::default());
     }
 
-    pub fn set_from_tensor(&mut self, img: Tensor, tensor: VectorizedTensor) {
+    /// `img` is the face shown on the source button
+    pub fn set_from_tensor(
+        &mut self,
+        img: impl Into<eframe::egui::ImageData>,
+        tensor: VectorizedTensor,
+    ) {
         self.texture.set(img, Default::default());
         self.data = tensor;
     }
+
+    pub fn set_from_embedding(&mut self, tensor: VectorizedTensor) {
+        self.texture.set(Image::default(), Default::default());
+        self.data = tensor;
+    }
+
+    pub fn clear(&mut self) {
+        self.set_from_embedding(VectorizedTensor::default());
+    }
 }
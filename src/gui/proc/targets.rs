@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+
+use crate::model::{data::VectorizedTensor, Embedding};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackRule {
+    /// Swapped with the active source
+    Swap,
+    Skip,
+    /// Swapped with a gallery entry
+    Source(String),
+}
+
+/// Rule of faces without one of their own, including untracked faces
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DefaultRule {
+    #[default]
+    Swap,
+    Keep,
+}
+
+impl From<DefaultRule> for TrackRule {
+    fn from(value: DefaultRule) -> Self {
+        match value {
+            DefaultRule::Swap => TrackRule::Swap,
+            DefaultRule::Keep => TrackRule::Skip,
+        }
+    }
+}
+
+/// Per track id swap choices made in the preview, reset with the tracker
+#[derive(Debug, Clone, Default)]
+pub struct TargetRules {
+    rules: HashMap<usize, TrackRule>,
+    /// Prepared vectors of gallery entries used by `TrackRule::Source`
+    sources: HashMap<String, VectorizedTensor>,
+    /// Assigned gallery embeddings the worker hasn't prepared yet
+    pending: HashMap<String, Embedding>,
+    default: DefaultRule,
+}
+
+impl TargetRules {
+    /// Clears per track rules and sources, keeping the default rule
+    pub fn reset(&mut self) {
+        *self = Self {
+            default: self.default,
+            ..Self::default()
+        };
+    }
+
+    pub fn rule(&self, track_id: Option<usize>) -> TrackRule {
+        match track_id.and_then(|id| self.rules.get(&id)) {
+            Some(rule) => rule.clone(),
+            None => self.default.into(),
+        }
+    }
+
+    pub fn default_rule(&self) -> DefaultRule {
+        self.default
+    }
+
+    pub fn set_default_rule(&mut self, rule: DefaultRule) {
+        self.default = rule;
+    }
+
+    /// Flips between swapped and skipped
+    pub fn toggle(&mut self, track_id: usize) {
+        let rule = match self.rule(Some(track_id)) {
+            TrackRule::Skip => TrackRule::Swap,
+            TrackRule::Swap | TrackRule::Source(_) => TrackRule::Skip,
+        };
+        self.rules.insert(track_id, rule);
+    }
+
+    pub fn set(&mut self, track_id: usize, rule: TrackRule) {
+        self.rules.insert(track_id, rule);
+    }
+
+    pub fn has_source(&self, id: &str) -> bool {
+        self.sources.contains_key(id) || self.pending.contains_key(id)
+    }
+
+    pub fn insert_source(&mut self, id: String, vector: VectorizedTensor) {
+        self.pending.remove(&id);
+        self.sources.insert(id, vector);
+    }
+
+    /// Leaves preparing the vector to the worker, see `take_pending`
+    pub fn queue_source(&mut self, id: String, embedding: Embedding) {
+        self.pending.insert(id, embedding);
+    }
+
+    pub fn take_pending(&mut self) -> Vec<(String, Embedding)> {
+        self.pending.drain().collect()
+    }
+
+    /// Source to swap the face with, `None` when it is skipped or its
+    /// gallery source isn't loaded
+    pub fn source_for(
+        &self,
+        track_id: Option<usize>,
+        active: &VectorizedTensor,
+    ) -> Option<VectorizedTensor> {
+        match self.rule(track_id) {
+            TrackRule::Swap => Some(active.clone()),
+            TrackRule::Skip => None,
+            TrackRule::Source(id) => self.sources.get(&id).cloned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::{data::VectorizedTensor, Embedding, EmbeddingNorm};
+
+    use super::{DefaultRule, TargetRules, TrackRule};
+
+    #[test]
+    fn unassigned_faces_follow_default_rule() {
+        let mut rules = TargetRules::default();
+        assert_eq!(rules.rule(Some(7)), TrackRule::Swap);
+        assert_eq!(rules.rule(None), TrackRule::Swap);
+
+        rules.toggle(7);
+        assert_eq!(rules.rule(Some(7)), TrackRule::Skip);
+        assert_eq!(rules.rule(Some(8)), TrackRule::Swap);
+
+        rules.set_default_rule(DefaultRule::Keep);
+        assert_eq!(rules.rule(Some(8)), TrackRule::Skip);
+        assert_eq!(rules.rule(None), TrackRule::Skip);
+        rules.toggle(8);
+        assert_eq!(rules.rule(Some(8)), TrackRule::Swap);
+
+        rules.reset();
+        assert_eq!(rules.default_rule(), DefaultRule::Keep);
+        assert_eq!(rules.rule(Some(7)), TrackRule::Skip);
+        assert_eq!(rules.rule(Some(8)), TrackRule::Skip);
+    }
+
+    #[test]
+    fn swaps_assigned_sources_once_loaded() {
+        let mut rules = TargetRules::default();
+        let active = VectorizedTensor::from(ndarray::arr2(&[[1., 0.]]));
+        let alice = VectorizedTensor::from(ndarray::arr2(&[[0., 1.]]));
+
+        rules.set(3, TrackRule::Source("alice".into()));
+        rules.queue_source(
+            "alice".into(),
+            Embedding::new("w600k_r50", 2, EmbeddingNorm::None),
+        );
+        assert!(rules.has_source("alice"));
+        assert!(rules.source_for(Some(3), &active).is_none());
+
+        let pending = rules.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert!(rules.take_pending().is_empty());
+
+        rules.insert_source(pending[0].0.clone(), alice);
+        assert!(rules.has_source("alice"));
+        assert_eq!(
+            rules.source_for(Some(3), &active).unwrap().0,
+            ndarray::arr2(&[[0., 1.]])
+        );
+        assert_eq!(
+            rules.source_for(Some(4), &active).unwrap().0,
+            ndarray::arr2(&[[1., 0.]])
+        );
+
+        rules.toggle(3);
+        assert!(rules.source_for(Some(3), &active).is_none());
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::PathBuf;
+
+use eframe::egui::{self, Align2, Vec2};
+
+use crate::{
+    cli::{IMAGE_EXTENSIONS, VIDEO_EXTENSIONS},
+    setting::OutputConfig,
+};
+
+use super::proc::RunTarget;
+
+pub enum RunDialogAction {
+    None,
+    Close,
+    Start(RunTarget, PathBuf),
+}
+
+/// Target and output picker shown before a run starts
+#[derive(Default)]
+pub struct RunDialog {
+    target: RunTarget,
+}
+
+impl RunDialog {
+    pub fn with_target(target: RunTarget) -> Self {
+        Self { target }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, config: &OutputConfig) -> RunDialogAction {
+        let mut action = RunDialogAction::None;
+        egui::Window::new("Run")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(Align2::CENTER_CENTER, Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.radio_value(&mut self.target, RunTarget::Camera, "Camera");
+                ui.horizontal(|ui| {
+                    let is_file = matches!(self.target, RunTarget::File(_));
+                    if ui.radio(is_file, "File").clicked() | ui.button("Browse").clicked() {
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Image or video", &media_extensions())
+                            .pick_file()
+                        {
+                            self.target = RunTarget::File(path);
+                        }
+                    }
+                    if ui
+                        .button("Frames folder")
+                        .on_hover_text("Folder of png or jpeg frames")
+                        .clicked()
+                    {
+                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                            self.target = RunTarget::File(dir);
+                        }
+                    }
+                });
+                if let RunTarget::File(path) = &self.target {
+                    ui.label(
+                        egui::RichText::new(path.display().to_string())
+                            .small()
+                            .monospace(),
+                    );
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Start").clicked() {
+                        let default_output = self.target.default_output(config);
+                        let mut dialog = rfd::FileDialog::new().set_file_name(&default_output);
+                        if let Some(dir) = &config.dir {
+                            dialog = dialog.set_directory(dir);
+                        }
+                        // frames go into a new folder inside the picked one
+                        let output = if self.target.is_sequence() {
+                            dialog.pick_folder().map(|dir| dir.join(default_output))
+                        } else {
+                            dialog.save_file()
+                        };
+                        if let Some(output) = output {
+                            action = RunDialogAction::Start(self.target.clone(), output);
+                        }
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = RunDialogAction::Close;
+                    }
+                });
+            });
+        action
+    }
+}
+
+fn media_extensions() -> Vec<&'static str> {
+    IMAGE_EXTENSIONS
+        .iter()
+        .chain(VIDEO_EXTENSIONS.iter())
+        .copied()
+        .collect()
+}
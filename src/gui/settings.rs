@@ -0,0 +1,274 @@
+use std::path::PathBuf;
+
+use eframe::egui::{self, Color32};
+
+use crate::setting::{Config, HotkeyConfig, ImageOutputFormat};
+
+use super::command::{Command, Keymap};
+
+pub enum SettingsAction {
+    None,
+    Close,
+    Apply(Config),
+}
+
+/// Edits a draft of the config, applied only once it validates
+pub struct SettingsWindow {
+    draft: Config,
+    errors: Vec<String>,
+}
+
+impl SettingsWindow {
+    pub fn new(config: &Config) -> Self {
+        let draft = config.clone();
+        Self {
+            errors: validate(&draft),
+            draft,
+        }
+    }
+
+    /// `can_apply` is false while the processor is busy
+    pub fn show(&mut self, ctx: &egui::Context, can_apply: bool) -> SettingsAction {
+        let mut action = SettingsAction::None;
+        let mut open = true;
+        let mut changed = false;
+
+        egui::Window::new("Settings")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                changed |= self.show_model(ui);
+                ui.separator();
+                changed |= self.show_gui(ui);
+                ui.separator();
+                changed |= self.show_hotkeys(ui);
+
+                for error in self.errors.iter() {
+                    ui.colored_label(Color32::RED, error);
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(
+                            can_apply && self.errors.is_empty(),
+                            egui::Button::new("Apply"),
+                        )
+                        .on_disabled_hover_text("Fix errors and stop running first")
+                        .clicked()
+                    {
+                        action = SettingsAction::Apply(self.draft.clone());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        action = SettingsAction::Close;
+                    }
+                });
+            });
+
+        if changed {
+            self.errors = validate(&self.draft);
+        }
+        if !open {
+            action = SettingsAction::Close;
+        }
+        action
+    }
+
+    fn show_model(&mut self, ui: &mut egui::Ui) -> bool {
+        let model = &mut self.draft.model;
+        let mut changed = false;
+
+        ui.heading("Model");
+        egui::Grid::new("settings_model")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Models folder");
+                changed |= path_edit(ui, &mut model.dir, PathKind::Folder);
+                ui.end_row();
+
+                for (label, path) in [
+                    ("Detector", &mut model.detector),
+                    ("Swapper", &mut model.swapper),
+                    ("Recognizer", &mut model.recognizer),
+                ] {
+                    ui.label(label);
+                    changed |= path_edit(ui, path, PathKind::Onnx);
+                    ui.end_row();
+                }
+
+                ui.label("Device");
+                egui::ComboBox::from_id_source("settings_device")
+                    .selected_text(if model.cuda { "CUDA" } else { "CPU" })
+                    .show_ui(ui, |ui| {
+                        changed |= ui.selectable_value(&mut model.cuda, false, "CPU").changed();
+                        changed |= ui.selectable_value(&mut model.cuda, true, "CUDA").changed();
+                    });
+                ui.end_row();
+
+                ui.label("Threads");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut model.threads).range(1..=64))
+                    .changed();
+                ui.end_row();
+
+                ui.label("Score threshold");
+                changed |= ui
+                    .add(egui::Slider::new(&mut model.score_threshold, 0.05..=1.))
+                    .changed();
+                ui.end_row();
+
+                ui.label("NMS threshold");
+                changed |= ui
+                    .add(egui::Slider::new(&mut model.nms_threshold, 0.05..=1.))
+                    .changed();
+                ui.end_row();
+
+                ui.label("Blend feather");
+                changed |= ui
+                    .add(egui::Slider::new(&mut model.blend_feather, 0..=64).suffix(" px"))
+                    .changed();
+                ui.end_row();
+            });
+        changed
+    }
+
+    fn show_gui(&mut self, ui: &mut egui::Ui) -> bool {
+        let gui = &mut self.draft.gui;
+        let mut changed = false;
+
+        ui.heading("Capture and output");
+        egui::Grid::new("settings_gui")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Camera");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut gui.camera).range(0..=16))
+                    .changed();
+                ui.end_row();
+
+                ui.label("FPS");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut gui.fps).range(1..=120))
+                    .changed();
+                ui.end_row();
+
+                ui.label("Output folder");
+                changed |= path_edit(ui, &mut gui.output.dir, PathKind::Folder);
+                ui.end_row();
+
+                ui.label("Captures folder");
+                changed |= path_edit(ui, &mut gui.output.captures, PathKind::Folder);
+                ui.end_row();
+
+                ui.label("Image format");
+                ui.horizontal(|ui| {
+                    for (format, label) in [
+                        (ImageOutputFormat::Png, "PNG"),
+                        (ImageOutputFormat::Jpeg, "JPEG"),
+                    ] {
+                        changed |= ui
+                            .radio_value(&mut gui.output.image_format, format, label)
+                            .changed();
+                    }
+                });
+                ui.end_row();
+
+                ui.label("Output window");
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .checkbox(&mut gui.output_window.chroma_key, "Chroma key")
+                        .on_hover_text("Background around the frame, black otherwise")
+                        .changed();
+                    ui.add_enabled_ui(gui.output_window.chroma_key, |ui| {
+                        changed |= ui
+                            .color_edit_button_srgb(&mut gui.output_window.key_color)
+                            .changed();
+                    });
+                });
+                ui.end_row();
+            });
+        changed
+    }
+}
+
+impl SettingsWindow {
+    fn show_hotkeys(&mut self, ui: &mut egui::Ui) -> bool {
+        let hotkeys = &mut self.draft.gui.hotkeys;
+        let mut changed = false;
+
+        egui::CollapsingHeader::new("Hotkeys").show(ui, |ui| {
+            egui::Grid::new("settings_hotkeys")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for command in Command::ALL {
+                        ui.label(command.label());
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(command.binding_mut(hotkeys))
+                                    .hint_text("none")
+                                    .desired_width(120.),
+                            )
+                            .changed();
+                        ui.end_row();
+                    }
+                });
+            if ui.button("Reset hotkeys").clicked() {
+                *hotkeys = HotkeyConfig::default();
+                changed = true;
+            }
+        });
+        changed
+    }
+}
+
+/// Config errors plus hotkeys that don't parse or conflict
+fn validate(config: &Config) -> Vec<String> {
+    let mut errors = config.validate();
+    errors.extend(Keymap::from_config(&config.gui.hotkeys).1);
+    errors
+}
+
+enum PathKind {
+    Folder,
+    Onnx,
+}
+
+/// Path text field with browse and clear buttons, empty means default
+fn path_edit(ui: &mut egui::Ui, path: &mut Option<PathBuf>, kind: PathKind) -> bool {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        let mut text = path
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        if ui
+            .add(egui::TextEdit::singleline(&mut text).hint_text("default"))
+            .changed()
+        {
+            *path = (!text.is_empty()).then(|| PathBuf::from(text));
+            changed = true;
+        }
+
+        if ui.button("Browse").clicked() {
+            let picked = match kind {
+                PathKind::Folder => rfd::FileDialog::new().pick_folder(),
+                PathKind::Onnx => rfd::FileDialog::new()
+                    .add_filter("Onnx model", &["onnx"])
+                    .pick_file(),
+            };
+            if let Some(picked) = picked {
+                *path = Some(picked);
+                changed = true;
+            }
+        }
+        if ui
+            .add_enabled(path.is_some(), egui::Button::new("Reset"))
+            .clicked()
+        {
+            *path = None;
+            changed = true;
+        }
+    });
+    changed
+}
@@ -0,0 +1,147 @@
+use eframe::egui::{self, Color32, Pos2, Rect, Sense, Stroke, TextureHandle, Vec2};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ViewMode {
+    #[default]
+    Output,
+    SideBySide,
+    Split,
+}
+
+impl ViewMode {
+    pub const ALL: [ViewMode; 3] = [ViewMode::Output, ViewMode::SideBySide, ViewMode::Split];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ViewMode::Output => "Output",
+            ViewMode::SideBySide => "Side by side",
+            ViewMode::Split => "Split",
+        }
+    }
+}
+
+/// Preview display state kept across frames
+pub struct FrameView {
+    pub mode: ViewMode,
+    /// Split divider position, 0 is all output and 1 all original
+    pub split: f32,
+    /// Debug overlay of detections over the output frame
+    pub overlay: bool,
+}
+
+impl Default for FrameView {
+    fn default() -> Self {
+        Self {
+            mode: ViewMode::default(),
+            split: 0.5,
+            overlay: false,
+        }
+    }
+}
+
+impl FrameView {
+    pub fn show_mode_selector(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for mode in ViewMode::ALL {
+                ui.selectable_value(&mut self.mode, mode, mode.label());
+            }
+            ui.separator();
+            ui.checkbox(&mut self.overlay, "Overlay");
+        });
+    }
+
+    /// Draws frames in the remaining space, returns the rect the output frame
+    /// is drawn in (image coordinates scale to it)
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        original: &TextureHandle,
+        output: &TextureHandle,
+    ) -> Option<Rect> {
+        let available = ui.available_rect_before_wrap();
+        let size = output.size_vec2();
+        if size.x <= 0. || size.y <= 0. {
+            ui.allocate_rect(available, Sense::hover());
+            return None;
+        }
+
+        let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1., 1.));
+        match self.mode {
+            ViewMode::Output => {
+                let rect = fit_rect(available, size);
+                ui.allocate_rect(available, Sense::hover());
+                ui.painter().image(output.id(), rect, uv, Color32::WHITE);
+                Some(rect)
+            }
+            ViewMode::SideBySide => {
+                let spacing = ui.spacing().item_spacing.x;
+                let half = Vec2::new((available.width() - spacing) * 0.5, available.height());
+                let left = fit_rect(Rect::from_min_size(available.min, half), size);
+                let right = fit_rect(
+                    Rect::from_min_size(available.min + Vec2::new(half.x + spacing, 0.), half),
+                    size,
+                );
+                ui.allocate_rect(available, Sense::hover());
+                ui.painter().image(original.id(), left, uv, Color32::WHITE);
+                ui.painter().image(output.id(), right, uv, Color32::WHITE);
+                Some(right)
+            }
+            ViewMode::Split => {
+                let rect = fit_rect(available, size);
+                ui.allocate_rect(available, Sense::hover());
+                let response =
+                    ui.interact(rect, ui.id().with("split_view"), Sense::click_and_drag());
+                if let Some(pointer) = response.interact_pointer_pos() {
+                    self.split = ((pointer.x - rect.left()) / rect.width()).clamp(0., 1.);
+                }
+                if response.hovered() || response.dragged() {
+                    ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+                }
+
+                let divider = rect.left() + rect.width() * self.split;
+                let painter = ui.painter();
+                painter.image(output.id(), rect, uv, Color32::WHITE);
+                painter
+                    .with_clip_rect(Rect::from_min_max(
+                        rect.min,
+                        Pos2::new(divider, rect.bottom()),
+                    ))
+                    .image(original.id(), rect, uv, Color32::WHITE);
+                painter.line_segment(
+                    [
+                        Pos2::new(divider, rect.top()),
+                        Pos2::new(divider, rect.bottom()),
+                    ],
+                    Stroke::new(2., Color32::WHITE),
+                );
+                painter.circle_filled(Pos2::new(divider, rect.center().y), 5., Color32::WHITE);
+                Some(rect)
+            }
+        }
+    }
+}
+
+/// Largest rect of `size` aspect ratio centered in `bounds`
+pub fn fit_rect(bounds: Rect, size: Vec2) -> Rect {
+    let scale = (bounds.width() / size.x).min(bounds.height() / size.y);
+    Rect::from_center_size(bounds.center(), size * scale)
+}
+
+#[cfg(test)]
+mod test {
+    use eframe::egui::{Pos2, Rect, Vec2};
+
+    use super::fit_rect;
+
+    #[test]
+    fn fits_frame_keeping_aspect_ratio() {
+        let bounds = Rect::from_min_size(Pos2::ZERO, Vec2::new(400., 400.));
+
+        let wide = fit_rect(bounds, Vec2::new(640., 480.));
+        assert_eq!(wide.size(), Vec2::new(400., 300.));
+        assert_eq!(wide.center(), bounds.center());
+
+        let tall = fit_rect(bounds, Vec2::new(100., 200.));
+        assert_eq!(tall.size(), Vec2::new(200., 400.));
+    }
+}
@@ -7,6 +7,12 @@ use crate::{
     result::Result,
 };
 
+pub use animation::{Animation, AnimationFrame, Looping};
+pub use sequence::{FramePattern, ImageSequence, SequenceFrame};
+
+pub mod animation;
+pub mod sequence;
+
 // RgbImage = ImageBuffer<Rgb<u8>, Vec<u8>>
 #[derive(Clone)]
 pub struct Image(pub image::RgbImage);
@@ -36,6 +42,23 @@ impl Image {
         Ok(Self(image))
     }
 
+    /// Decodes encoded image bytes (format guessed from content)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(Self(
+            image::load_from_memory(bytes)
+                .map_err(Error::ImageError)?
+                .to_rgb8(),
+        ))
+    }
+
+    pub fn encode(&self, format: image::ImageFormat) -> Result<Vec<u8>> {
+        let mut bytes = std::io::Cursor::new(vec![]);
+        self.0
+            .write_to(&mut bytes, format)
+            .map_err(Error::ImageError)?;
+        Ok(bytes.into_inner())
+    }
+
     pub fn resize(&self, size: (u32, u32)) -> Self {
         let (cur_x, cur_y) = self.dimensions();
         if size.0 == cur_x && size.1 == cur_y {
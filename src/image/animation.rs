@@ -0,0 +1,322 @@
+use std::{
+    io::{BufWriter, Cursor},
+    path::Path,
+    time::Duration,
+};
+
+use image::{AnimationDecoder, ImageFormat};
+
+use crate::{error::Error, result::Result};
+
+use super::Image;
+
+/// Loop behavior of animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Looping {
+    /// Plays once (no loop extension)
+    Once,
+    /// Plays once then repeats n more times
+    Repeat(u16),
+    Infinite,
+}
+
+#[derive(Clone)]
+pub struct AnimationFrame {
+    pub buffer: image::RgbaImage,
+    pub delay: Duration,
+}
+
+impl AnimationFrame {
+    pub fn image(&self) -> Image {
+        Image::from(image::DynamicImage::ImageRgba8(self.buffer.clone()).to_rgb8())
+    }
+}
+
+/// Decoded animated gif | apng | webp
+#[derive(Clone)]
+pub struct Animation {
+    pub frames: Vec<AnimationFrame>,
+    pub looping: Looping,
+}
+
+impl Animation {
+    pub fn is_animated(path: &Path) -> Result<bool> {
+        let Ok(format) = ImageFormat::from_path(path) else {
+            return Ok(false);
+        };
+        let reader =
+            std::io::BufReader::new(std::fs::File::open(path).map_err(Error::as_unknown_error)?);
+
+        Ok(match format {
+            ImageFormat::Gif => {
+                image::codecs::gif::GifDecoder::new(reader)
+                    .map_err(Error::ImageError)?
+                    .into_frames()
+                    .take(2)
+                    .count()
+                    > 1
+            }
+            ImageFormat::Png => image::codecs::png::PngDecoder::new(reader)
+                .map_err(Error::ImageError)?
+                .is_apng()
+                .map_err(Error::ImageError)?,
+            ImageFormat::WebP => image::codecs::webp::WebPDecoder::new(reader)
+                .map_err(Error::ImageError)?
+                .has_animation(),
+            _ => false,
+        })
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let format = ImageFormat::from_path(path).map_err(Error::ImageError)?;
+        let bytes = std::fs::read(path).map_err(Error::as_unknown_error)?;
+        let reader = Cursor::new(bytes.as_slice());
+
+        let (frames, looping) = match format {
+            ImageFormat::Gif => (
+                image::codecs::gif::GifDecoder::new(reader)
+                    .map_err(Error::ImageError)?
+                    .into_frames()
+                    .collect_frames(),
+                gif_looping(&bytes),
+            ),
+            ImageFormat::Png => (
+                image::codecs::png::PngDecoder::new(reader)
+                    .map_err(Error::ImageError)?
+                    .apng()
+                    .map_err(Error::ImageError)?
+                    .into_frames()
+                    .collect_frames(),
+                apng_looping(&bytes),
+            ),
+            ImageFormat::WebP => (
+                image::codecs::webp::WebPDecoder::new(reader)
+                    .map_err(Error::ImageError)?
+                    .into_frames()
+                    .collect_frames(),
+                webp_looping(&bytes),
+            ),
+            _ => {
+                return Err(Error::UnknownError(
+                    format!("Unsupported animation format: {}", path.display()).into(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            frames: frames
+                .map_err(Error::ImageError)?
+                .into_iter()
+                .map(|frame| AnimationFrame {
+                    delay: Duration::from(frame.delay()),
+                    buffer: frame.into_buffer(),
+                })
+                .collect(),
+            looping,
+        })
+    }
+
+    /// Runs f on every frame keeping delay and original alpha channel
+    pub fn try_map<F>(mut self, mut f: F) -> Result<Self>
+    where
+        F: FnMut(Image) -> Result<Image>,
+    {
+        for frame in self.frames.iter_mut() {
+            let processed = f(frame.image())?;
+            let original = &frame.buffer;
+            let same_dim = processed.dimensions() == original.dimensions();
+            let (w, h) = processed.dimensions();
+            frame.buffer = image::RgbaImage::from_fn(w, h, |x, y| {
+                let [r, g, b] = processed[(x, y)].0;
+                let alpha = if same_dim {
+                    original[(x, y)][3]
+                } else {
+                    u8::MAX
+                };
+                image::Rgba([r, g, b, alpha])
+            });
+        }
+        Ok(self)
+    }
+
+    /// Fails unless `path` is a gif, checked before decoding so a bad output
+    /// doesn't waste a full run
+    pub fn check_output(path: &Path) -> Result<()> {
+        if ImageFormat::from_path(path).ok() != Some(ImageFormat::Gif) {
+            return Err(Error::UnknownError(
+                format!("Animated output only supports gif: {}", path.display()).into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encodes as animated gif (only animated encoder available)
+    pub fn save(&self, path: &Path) -> Result<()> {
+        use image::codecs::gif::{GifEncoder, Repeat};
+
+        Self::check_output(path)?;
+
+        let file = std::fs::File::create(path).map_err(Error::as_unknown_error)?;
+        let mut encoder = GifEncoder::new(BufWriter::new(file));
+        match self.looping {
+            Looping::Once => {}
+            Looping::Repeat(count) => encoder
+                .set_repeat(Repeat::Finite(count))
+                .map_err(Error::ImageError)?,
+            Looping::Infinite => encoder
+                .set_repeat(Repeat::Infinite)
+                .map_err(Error::ImageError)?,
+        }
+
+        encoder
+            .encode_frames(self.frames.iter().map(|frame| {
+                image::Frame::from_parts(
+                    frame.buffer.clone(),
+                    0,
+                    0,
+                    image::Delay::from_saturating_duration(frame.delay),
+                )
+            }))
+            .map_err(Error::ImageError)
+    }
+}
+
+fn find_chunk(bytes: &[u8], tag: &[u8]) -> Option<usize> {
+    bytes.windows(tag.len()).position(|window| window == tag)
+}
+
+// Total plays where 0 = infinite (apng | webp)
+fn looping_from_plays(plays: u32) -> Looping {
+    match plays {
+        0 => Looping::Infinite,
+        1 => Looping::Once,
+        n => Looping::Repeat((n - 1).min(u16::MAX as u32) as u16),
+    }
+}
+
+// NETSCAPE2.0 application extension | 0x03 0x01 count(u16 le)
+fn gif_looping(bytes: &[u8]) -> Looping {
+    let Some(idx) = find_chunk(bytes, b"NETSCAPE2.0") else {
+        return Looping::Once;
+    };
+    match bytes.get(idx + 11..idx + 15) {
+        Some([0x03, 0x01, lo, hi]) => match u16::from_le_bytes([*lo, *hi]) {
+            0 => Looping::Infinite,
+            count => Looping::Repeat(count),
+        },
+        _ => Looping::Once,
+    }
+}
+
+// acTL | num_frames(u32 be) num_plays(u32 be)
+fn apng_looping(bytes: &[u8]) -> Looping {
+    find_chunk(bytes, b"acTL")
+        .and_then(|idx| bytes.get(idx + 8..idx + 12))
+        .map(|plays| {
+            looping_from_plays(u32::from_be_bytes([plays[0], plays[1], plays[2], plays[3]]))
+        })
+        .unwrap_or(Looping::Infinite)
+}
+
+// ANIM | chunk size(u32 le) background(u32) loop count(u16 le)
+fn webp_looping(bytes: &[u8]) -> Looping {
+    find_chunk(bytes, b"ANIM")
+        .and_then(|idx| bytes.get(idx + 12..idx + 14))
+        .map(|count| looping_from_plays(u16::from_le_bytes([count[0], count[1]]) as u32))
+        .unwrap_or(Looping::Infinite)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{apng_looping, gif_looping, webp_looping, Animation, AnimationFrame, Looping};
+
+    #[test]
+    fn reads_gif_loop_extension() {
+        let mut bytes = b"GIF89a....!\xFF\x0BNETSCAPE2.0".to_vec();
+        assert_eq!(gif_looping(&bytes), Looping::Once);
+
+        bytes.extend_from_slice(&[0x03, 0x01, 0x00, 0x00]);
+        assert_eq!(gif_looping(&bytes), Looping::Infinite);
+
+        let len = bytes.len();
+        bytes[len - 2] = 3;
+        assert_eq!(gif_looping(&bytes), Looping::Repeat(3));
+        assert_eq!(gif_looping(b"GIF89a"), Looping::Once);
+    }
+
+    #[test]
+    fn reads_apng_and_webp_play_count() {
+        let mut apng = b"\x00\x00\x00\x08acTL".to_vec();
+        apng.extend_from_slice(&10u32.to_be_bytes());
+        apng.extend_from_slice(&2u32.to_be_bytes());
+        assert_eq!(apng_looping(&apng), Looping::Repeat(1));
+
+        let mut webp = b"RIFF....WEBPVP8XANIM".to_vec();
+        webp.extend_from_slice(&6u32.to_le_bytes());
+        webp.extend_from_slice(&[0xFF; 4]);
+        webp.extend_from_slice(&0u16.to_le_bytes());
+        assert_eq!(webp_looping(&webp), Looping::Infinite);
+
+        let len = webp.len();
+        webp[len - 2] = 1;
+        assert_eq!(webp_looping(&webp), Looping::Once);
+    }
+
+    #[test]
+    fn round_trips_gif() {
+        let dir = std::env::temp_dir().join(format!("noface_animation_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (input, output) = (dir.join("input.gif"), dir.join("output.gif"));
+
+        let delays = [40, 100, 250].map(Duration::from_millis);
+        Animation {
+            frames: delays
+                .iter()
+                .enumerate()
+                .map(|(idx, delay)| AnimationFrame {
+                    buffer: image::RgbaImage::from_pixel(
+                        8,
+                        6,
+                        image::Rgba([idx as u8 * 80, 0, 0, 255]),
+                    ),
+                    delay: *delay,
+                })
+                .collect(),
+            looping: Looping::Repeat(2),
+        }
+        .save(&input)
+        .unwrap();
+
+        let decoded = Animation::from_path(&input).unwrap();
+        assert_eq!(decoded.frames.len(), delays.len());
+        decoded
+            .try_map(|mut img| {
+                image::imageops::invert(&mut img.0);
+                Ok(img)
+            })
+            .unwrap()
+            .save(&output)
+            .unwrap();
+
+        let result = Animation::from_path(&output).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+        assert_eq!(result.looping, Looping::Repeat(2));
+        assert_eq!(
+            result
+                .frames
+                .iter()
+                .map(|frame| frame.delay)
+                .collect::<Vec<_>>(),
+            delays
+        );
+        assert!(result
+            .frames
+            .iter()
+            .all(|frame| frame.buffer.dimensions() == (8, 6)));
+        assert_eq!(result.frames[0].buffer[(0, 0)].0, [255, 255, 255, 255]);
+        assert!(Animation::check_output(&dir.join("output.png")).is_err());
+    }
+}
@@ -0,0 +1,329 @@
+use std::path::{Path, PathBuf};
+
+use crate::{error::Error, result::Result};
+
+use super::Image;
+
+const SEQUENCE_EXTENSIONS: [&str; 3] = ["png", "jpg", "jpeg"];
+
+/// Printf style frame pattern (`shot_%05d.png` | `shot_%d.png`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramePattern {
+    prefix: String,
+    suffix: String,
+    width: usize,
+}
+
+impl FramePattern {
+    pub fn parse(file_name: &str) -> Option<Self> {
+        let start = file_name.find('%')?;
+        let rest = &file_name[start + 1..];
+        let end = rest.find('d')?;
+        let spec = &rest[..end];
+
+        let width = match spec {
+            "" => 0,
+            _ if spec.starts_with('0') => spec[1..].parse().ok()?,
+            _ => return None,
+        };
+
+        Some(Self {
+            prefix: file_name[..start].to_string(),
+            suffix: rest[end + 1..].to_string(),
+            width,
+        })
+    }
+
+    pub fn format(&self, number: u64) -> String {
+        format!(
+            "{}{:0width$}{}",
+            self.prefix,
+            number,
+            self.suffix,
+            width = self.width
+        )
+    }
+
+    pub fn number_of(&self, file_name: &str) -> Option<u64> {
+        let digits = file_name
+            .strip_prefix(&self.prefix)?
+            .strip_suffix(&self.suffix)?;
+
+        if digits.is_empty()
+            || !digits.chars().all(|c| c.is_ascii_digit())
+            || (self.width != 0 && digits.len() < self.width)
+        {
+            return None;
+        }
+        digits.parse().ok()
+    }
+}
+
+/// Frame read from sequence with the color type it was stored in
+pub struct SequenceFrame {
+    pub path: PathBuf,
+    pub image: Image,
+    pub color: image::ColorType,
+}
+
+/// Ordered list of frame files from a directory or printf style pattern
+#[derive(Debug, Clone, Default)]
+pub struct ImageSequence {
+    paths: Vec<PathBuf>,
+}
+
+impl ImageSequence {
+    /// Directory or pattern (`dir/shot_%05d.png`)
+    pub fn from_path(path: PathBuf) -> Result<Self> {
+        if path.is_dir() {
+            return Self::from_dir(path);
+        }
+        Self::from_pattern(path)
+    }
+
+    pub fn is_sequence(path: &Path) -> bool {
+        path.is_dir()
+            || path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(FramePattern::parse)
+                .is_some()
+    }
+
+    pub fn from_dir(dir: PathBuf) -> Result<Self> {
+        let mut paths = std::fs::read_dir(&dir)
+            .map_err(Error::as_unknown_error)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && is_sequence_file(path))
+            .collect::<Vec<PathBuf>>();
+
+        paths.sort_by_key(|path| sort_key(path));
+        Self::non_empty(paths, &dir)
+    }
+
+    pub fn from_pattern(pattern: PathBuf) -> Result<Self> {
+        let Some(frame_pattern) = pattern
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(FramePattern::parse)
+        else {
+            return Err(Error::UnknownError(
+                format!("Invalid frame pattern: {}", pattern.display()).into(),
+            ));
+        };
+
+        let dir = match pattern.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        let mut frames = std::fs::read_dir(&dir)
+            .map_err(Error::as_unknown_error)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let number = frame_pattern.number_of(path.file_name()?.to_str()?)?;
+                Some((number, path))
+            })
+            .collect::<Vec<(u64, PathBuf)>>();
+
+        frames.sort_by_key(|(number, _)| *number);
+        Self::non_empty(frames.into_iter().map(|(_, path)| path).collect(), &pattern)
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn frames(&self) -> impl Iterator<Item = Result<SequenceFrame>> + '_ {
+        self.paths.iter().map(|path| Self::read_frame(path))
+    }
+
+    pub fn read_frame(path: &Path) -> Result<SequenceFrame> {
+        let dyn_image = image::open(path).map_err(Error::ImageError)?;
+        Ok(SequenceFrame {
+            path: path.to_path_buf(),
+            color: dyn_image.color(),
+            image: Image::from(dyn_image.to_rgb8()),
+        })
+    }
+
+    /// Errors when `out_dir` is the folder the frames are read from, writing
+    /// there would replace them
+    pub fn ensure_output_dir(&self, out_dir: &Path) -> Result<()> {
+        let source_dir = self
+            .paths
+            .first()
+            .and_then(|path| path.parent())
+            .and_then(|dir| dir.canonicalize().ok());
+        if source_dir.is_some() && out_dir.canonicalize().ok() == source_dir {
+            return Err(Error::UnknownError(
+                format!(
+                    "Output directory {} contains the input frames",
+                    out_dir.display()
+                )
+                .into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Writes processed frame into out_dir with the source frame name.
+    /// 16 bit sources are written back as 16 bit when output format is png,
+    /// processing is 8 bit so that is an upconversion and the source's extra
+    /// precision is lost.
+    pub fn write_frame(frame: &SequenceFrame, image: &Image, out_dir: &Path) -> Result<PathBuf> {
+        let Some(file_name) = frame.path.file_name() else {
+            return Err(Error::UnknownError(
+                format!("Invalid frame path: {}", frame.path.display()).into(),
+            ));
+        };
+        std::fs::create_dir_all(out_dir).map_err(Error::as_unknown_error)?;
+        let out_path = out_dir.join(file_name);
+        Self::write_frame_to(frame, image, &out_path)?;
+        Ok(out_path)
+    }
+
+    /// Like `write_frame` with an explicit output file, 16 bit png output is
+    /// the 8 bit result widened
+    pub fn write_frame_to(frame: &SequenceFrame, image: &Image, out_path: &Path) -> Result<()> {
+        let is_png = out_path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+        let is_high_depth = frame.color.bytes_per_pixel() / frame.color.channel_count() > 1;
+
+        if is_png && is_high_depth {
+            image::DynamicImage::ImageRgb8(image.0.clone())
+                .to_rgb16()
+                .save(out_path)
+                .map_err(Error::ImageError)
+        } else {
+            image.save(out_path).map_err(Error::ImageError)
+        }
+    }
+
+    fn non_empty(paths: Vec<PathBuf>, origin: &Path) -> Result<Self> {
+        if paths.is_empty() {
+            return Err(Error::UnknownError(
+                format!("No frames found in: {}", origin.display()).into(),
+            ));
+        }
+        Ok(Self { paths })
+    }
+}
+
+fn is_sequence_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            SEQUENCE_EXTENSIONS
+                .iter()
+                .any(|supported| ext.eq_ignore_ascii_case(supported))
+        })
+}
+
+// (stem without trailing digits, trailing number, file name) so frame_2 < frame_10
+fn sort_key(path: &Path) -> (String, u64, String) {
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let base = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+    let number = stem[base.len()..].parse().unwrap_or(0);
+    let file_name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    (base.to_string(), number, file_name.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::image::Image;
+
+    use super::{sort_key, FramePattern, ImageSequence};
+
+    #[test]
+    fn can_parse_and_format_frame_pattern() {
+        let pattern = FramePattern::parse("shot_%05d.png").expect("Failed to parse pattern");
+
+        assert_eq!(pattern.format(42), "shot_00042.png");
+        assert_eq!(pattern.number_of("shot_00042.png"), Some(42));
+        assert_eq!(pattern.number_of("shot_42.png"), None);
+        assert_eq!(pattern.number_of("shot_00042.jpg"), None);
+
+        let unpadded = FramePattern::parse("frame%d.jpg").expect("Failed to parse pattern");
+        assert_eq!(unpadded.format(7), "frame7.jpg");
+        assert_eq!(unpadded.number_of("frame123.jpg"), Some(123));
+
+        assert_eq!(FramePattern::parse("shot.png"), None);
+        assert_eq!(FramePattern::parse("shot_%5s.png"), None);
+    }
+
+    #[test]
+    fn sorts_frames_numerically() {
+        let mut paths = ["frame_10.png", "frame_2.png", "frame_1.png"]
+            .map(PathBuf::from)
+            .to_vec();
+        paths.sort_by_key(|path| sort_key(path));
+
+        assert_eq!(
+            paths,
+            ["frame_1.png", "frame_2.png", "frame_10.png"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn round_trips_frame_directory() {
+        let dir = std::env::temp_dir().join(format!("noface_sequence_{}", std::process::id()));
+        let (input, output) = (dir.join("input"), dir.join("output"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&input).unwrap();
+
+        image::RgbImage::from_pixel(4, 2, image::Rgb([10, 20, 30]))
+            .save(input.join("shot_10.png"))
+            .unwrap();
+        image::RgbImage::from_pixel(4, 2, image::Rgb([40, 50, 60]))
+            .save(input.join("shot_2.png"))
+            .unwrap();
+        image::ImageBuffer::<image::Rgb<u16>, _>::from_pixel(4, 2, image::Rgb([1000, 0, 0]))
+            .save(input.join("shot_1.png"))
+            .unwrap();
+        std::fs::write(input.join("notes.txt"), "not a frame").unwrap();
+
+        let sequence = ImageSequence::from_path(input.clone()).unwrap();
+        assert_eq!(sequence.len(), 3);
+        assert!(sequence.ensure_output_dir(&input).is_err());
+        assert!(sequence.ensure_output_dir(&output).is_ok());
+
+        for frame in sequence.frames() {
+            let frame = frame.unwrap();
+            let gray = image::imageops::grayscale(&frame.image.0);
+            let processed = Image(image::DynamicImage::ImageLuma8(gray).to_rgb8());
+            ImageSequence::write_frame(&frame, &processed, &output).unwrap();
+        }
+
+        let written = ImageSequence::from_path(output.join("shot_%d.png")).unwrap();
+        let names = written
+            .paths()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["shot_1.png", "shot_2.png", "shot_10.png"]);
+        // 16 bit source stays 16 bit
+        let first = ImageSequence::read_frame(&written.paths()[0]).unwrap();
+        assert_eq!(first.color, image::ColorType::Rgb16);
+        assert_eq!(first.image.dimensions(), (4, 2));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,13 +1,21 @@
+pub mod cli;
 pub mod cv;
 pub mod error;
+#[cfg(feature = "capi")]
+pub mod ffi;
 pub mod gui;
 pub mod image;
 pub mod math;
 pub mod model;
+#[cfg(feature = "python")]
+pub mod python;
 pub mod result;
+pub mod server;
 pub mod setting;
+pub mod swapper;
 pub mod sync;
 pub mod tracing;
 
 pub use error::Error;
 pub use result::Result;
+pub use swapper::Swapper;
@@ -1,4 +1,8 @@
+use std::process::ExitCode;
+
+use clap::Parser;
 use noface::{
+    cli::Cli,
     gui::Gui,
     model::register_ort,
     result::Result,
@@ -7,13 +11,31 @@ use noface::{
 };
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    init_subscriber(get_subscriber("noface", "off", std::io::stdout))?;
-    // Get Setting
+async fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("noface: {}", err);
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let Some(command) = cli.command else {
+        init_subscriber(get_subscriber("noface", "off", std::io::stdout))?;
+        // Get Setting
+        let setting = Setting::get()?;
+        // Register Models
+        register_ort(&setting.config.model)?;
+        // Gui Create and Run
+        let gui = Gui::new(setting);
+        return gui.run();
+    };
+
+    // Headless, keep stdout for command output
+    init_subscriber(get_subscriber("noface", "off", std::io::stderr))?;
     let setting = Setting::get()?;
-    // Register Models
     register_ort(&setting.config.model)?;
-    // Gui Create and Run
-    let gui = Gui::new(setting);
-    gui.run()
+    command.run(setting)
 }
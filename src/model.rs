@@ -1,16 +1,27 @@
-use data::VectorizedTensor;
+use data::{Face, VectorizedTensor};
 use detection_model::DetectionModel;
 use swap_model::SwapModel;
 use vectorization_model::VectorizationModel;
 
 use crate::{Error, Result};
 pub use data::{RecgnData, Tensor, TensorData};
+pub use embedding::{Embedding, EmbeddingNorm, EmbeddingSource};
+pub use selection::{FaceSelection, SourceFace};
+pub use timing::{Stage, StageStats, StageTimings, TimingHistory};
 
 mod detection_model;
+mod selection;
 mod swap_model;
 mod vectorization_model;
 
 pub mod data;
+pub mod embedding;
+pub mod timing;
+
+/// Default recognition model, embeddings are named after the configured
+/// recognizer file
+pub const VECTORIZATION_MODEL_NAME: &str = "w600k_r50";
+pub const VECTORIZATION_DIM: usize = 512;
 
 type InputSizeMatrix = ndarray::Array<(usize, usize, usize, usize), ndarray::Dim<[usize; 4]>>;
 
@@ -23,71 +34,189 @@ pub struct Model {
     detect: DetectionModel,
     swap: SwapModel,
     vec: VectorizationModel,
+    /// Recognizer file stem stored with embeddings
+    vec_name: String,
     cuda: Option<ArcCudaDevice>,
+    blend_feather: usize,
+    /// Stage times since the last `take_timings`
+    timings: StageTimings,
 }
 
 impl Model {
     //might want thread count etc from config
     #[tracing::instrument(name = "Initializing Models", skip(config), err)]
     pub fn new(config: &crate::setting::ModelConfig) -> Result<Self> {
-        let model_base_path = std::env::current_dir()
-            .map_err(Error::as_unknown_error)?
-            .join("models");
-
-        Ok(Self {
-            detect: DetectionModel::new(model_base_path.join("det_10g.onnx"))?,
-            swap: SwapModel::new(model_base_path.join("inswapper_128.onnx"))?,
-            vec: VectorizationModel::new(model_base_path.join("w600k_r50.onnx"))?,
+        let recognizer_path = config.recognizer_path()?;
+        let mut model = Self {
+            detect: DetectionModel::new(config.detector_path()?, config)?,
+            swap: SwapModel::new(config.swapper_path()?, config)?,
+            vec_name: recognizer_path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| VECTORIZATION_MODEL_NAME.into()),
+            vec: VectorizationModel::new(recognizer_path, config)?,
             cuda: config
                 .cuda
                 .then_some(cudarc::driver::CudaDevice::new(0).map_err(Error::CudaError)?),
-        })
+            blend_feather: 0,
+            timings: StageTimings::default(),
+        };
+        model.set_options(config);
+        Ok(model)
+    }
+
+    /// Applies options that don't need new sessions (thresholds, blending)
+    pub fn set_options(&mut self, config: &crate::setting::ModelConfig) {
+        self.detect.threshold = config.score_threshold;
+        self.detect.nms_threshold = config.nms_threshold;
+        self.blend_feather = config.blend_feather;
     }
 
     pub fn run(&mut self, mut tar: Tensor, src: VectorizedTensor) -> Result<Tensor> {
-        let faces = self.detect.run(tar.clone(), self.cuda.as_ref())?;
-        if faces.is_empty() {
-            return Ok(tar);
+        let faces = self.detect(tar.clone())?;
+        if let Some(face) = faces.first() {
+            self.swap_face(&mut tar, face, src)?;
         }
+        Ok(tar)
+    }
 
-        let face = faces[0].crop(&tar, Some(1.));
-        let swapped_tar = self.swap.run(face, src, self.cuda.as_ref())?;
-
-        let (_, bbox) = faces[0].get_scaled_bbox(1.);
+    /// Stage times recorded since the last call, also traced as `stage` spans
+    pub fn take_timings(&mut self) -> StageTimings {
+        std::mem::take(&mut self.timings)
+    }
 
-        tar.transpose(
-            swapped_tar,
-            (
-                bbox.0 as usize,
-                bbox.1 as usize,
-                bbox.2 as usize,
-                bbox.3 as usize,
-            ),
-        )?;
+    pub fn detect(&mut self, tar: Tensor) -> Result<Vec<Face>> {
+        timing::measure(&mut self.timings, Stage::Detect, || {
+            self.detect.run(tar, self.cuda.as_ref())
+        })
+    }
 
-        Ok(tar)
+    pub fn swap_face(
+        &mut self,
+        tar: &mut Tensor,
+        face: &Face,
+        src: VectorizedTensor,
+    ) -> Result<()> {
+        let crop = timing::measure(&mut self.timings, Stage::Align, || face.crop(tar, Some(1.)));
+        let swapped_tar = timing::measure(&mut self.timings, Stage::Swap, || {
+            self.swap.run(crop, src, self.cuda.as_ref())
+        })?;
+
+        let (_, bbox) = face.get_scaled_bbox(1.);
+
+        timing::measure(&mut self.timings, Stage::Paste, || {
+            tar.transpose_feathered(
+                swapped_tar,
+                (
+                    bbox.0 as usize,
+                    bbox.1 as usize,
+                    bbox.2 as usize,
+                    bbox.3 as usize,
+                ),
+                self.blend_feather,
+            )
+        })
     }
 
     pub fn vectorize_tensor(&mut self, data: Tensor) -> Result<(Tensor, VectorizedTensor)> {
-        let faces = self.detect.run(data.clone(), self.cuda.as_ref())?;
+        self.vectorize_tensor_with(data, &FaceSelection::default())
+    }
 
-        if faces.is_empty() {
-            return Err(Error::InvalidModelIOError("No Face detected".into()));
-        }
+    pub fn vectorize_tensor_with(
+        &mut self,
+        data: Tensor,
+        selection: &FaceSelection,
+    ) -> Result<(Tensor, VectorizedTensor)> {
+        let (face_tensor, raw) = self.embed_tensor_with(data, selection)?;
+        Ok((face_tensor, self.prep_embedding(&raw)))
+    }
 
-        let face_tensor = faces[0].crop_aligned(&data, Some(1.));
+    /// Aligned face and raw recognition vector (before swap preparation)
+    pub fn embed_tensor(&mut self, data: Tensor) -> Result<(Tensor, VectorizedTensor)> {
+        self.embed_tensor_with(data, &FaceSelection::default())
+    }
 
-        let vec_tensor = self
-            .vec
-            .run(face_tensor.clone(), self.cuda.as_ref())?
-            .prep_for_swap(&self.swap.graph.output);
+    /// First selected aligned face and the selected faces' averaged raw vector
+    pub fn embed_tensor_with(
+        &mut self,
+        data: Tensor,
+        selection: &FaceSelection,
+    ) -> Result<(Tensor, VectorizedTensor)> {
+        let mut faces = self.embed_selected(data, selection)?;
+        let raw = match faces.len() {
+            1 => faces[0].raw.clone(),
+            _ => VectorizedTensor::mean(
+                &faces
+                    .iter()
+                    .map(|face| face.raw.clone())
+                    .collect::<Vec<_>>(),
+            )
+            .ok_or_else(|| Error::InvalidModelIOError("No face selected".into()))?,
+        };
+        Ok((faces.swap_remove(0).tensor, raw))
+    }
+
+    /// Every detected face with its aligned crop and raw vector, for picking
+    /// a source out of group photos
+    pub fn embed_faces(&mut self, data: Tensor) -> Result<Vec<SourceFace>> {
+        self.embed_selected(data, &FaceSelection::All)
+    }
+
+    /// Selected faces in selection order, only those are run through
+    /// recognition
+    pub fn embed_selected(
+        &mut self,
+        data: Tensor,
+        selection: &FaceSelection,
+    ) -> Result<Vec<SourceFace>> {
+        let faces = self.detect(data.clone())?;
+        selection
+            .resolve(&faces)?
+            .into_iter()
+            .map(|idx| {
+                let face = faces[idx].clone();
+                let tensor = timing::measure(&mut self.timings, Stage::Align, || {
+                    face.crop_aligned(&data, Some(1.))
+                });
+                let raw = timing::measure(&mut self.timings, Stage::Embed, || {
+                    self.vec.run(tensor.clone(), self.cuda.as_ref())
+                })?;
+                Ok(SourceFace { face, tensor, raw })
+            })
+            .collect()
+    }
+
+    /// Raw recognition vector to swap model source input
+    pub fn prep_embedding(&self, raw: &VectorizedTensor) -> VectorizedTensor {
+        raw.prep_for_swap(&self.swap.graph.output)
+    }
 
-        Ok((face_tensor, vec_tensor))
+    /// Name embeddings made with this model are stored under
+    pub fn recognizer_name(&self) -> &str {
+        &self.vec_name
+    }
+
+    pub fn source_from_embedding(&self, embedding: &Embedding) -> Result<VectorizedTensor> {
+        if embedding.meta.model != self.vec_name || embedding.meta.dim != VECTORIZATION_DIM {
+            return Err(Error::InvalidModelIOError(format!(
+                "Embedding from {} ({}d) doesn't match {} ({}d)",
+                embedding.meta.model, embedding.meta.dim, self.vec_name, VECTORIZATION_DIM
+            )));
+        }
+        Ok(self.prep_embedding(&embedding.mean()?))
     }
 }
 
+static ORT_REGISTERED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Commits global onnx runtime environment, later calls are no-op
 #[tracing::instrument(err)]
 pub fn register_ort(config: &crate::setting::ModelConfig) -> Result<()> {
+    use std::sync::atomic::Ordering;
+    if ORT_REGISTERED.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+
     let onnx_env = ort::init().with_name("noface_image_procesor");
 
     let onnx_env = match config.cuda {
@@ -98,13 +227,26 @@ pub fn register_ort(config: &crate::setting::ModelConfig) -> Result<()> {
     };
 
     onnx_env.commit().map_err(Error::ModelError)?;
+    ORT_REGISTERED.store(true, Ordering::SeqCst);
     Ok(())
 }
 
-fn start_session_from_file(onnx_path: std::path::PathBuf) -> Result<ort::Session> {
-    ort::Session::builder()
+fn start_session_from_file(
+    onnx_path: std::path::PathBuf,
+    config: &crate::setting::ModelConfig,
+) -> Result<ort::Session> {
+    let builder = ort::Session::builder()
         .map_err(Error::ModelError)?
-        .with_intra_threads(4)
+        .with_intra_threads(config.threads)
+        .map_err(Error::ModelError)?;
+
+    // per session so a reload can switch devices after the env is committed
+    let provider = match config.cuda {
+        true => ort::CUDAExecutionProvider::default().build(),
+        false => ort::CPUExecutionProvider::default().build(),
+    };
+    builder
+        .with_execution_providers([provider])
         .map_err(Error::ModelError)?
         .commit_from_file(onnx_path)
         .map_err(Error::ModelError)
@@ -1,16 +1,21 @@
 pub use keypoints::KeyPoints;
+pub use tracker::Tracker;
 
 use super::Tensor;
 
 pub mod keypoints;
+pub mod tracker;
 
 pub type BBox = (f32, f32, f32, f32);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct Face {
     pub score: f32,
     pub keypoints: KeyPoints,
     pub bbox: BBox,
+    /// Assigned by tracker across frames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub track_id: Option<usize>,
 }
 
 impl Face {
@@ -11,7 +11,7 @@ const ARC_FACE_DST: KeyPoints = KeyPoints([
     [70.7299, 92.2041],
 ]);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct KeyPoints(pub [[f32; 2]; KEY_POINTS_LEN]);
 
 impl KeyPoints {
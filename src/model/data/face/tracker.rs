@@ -0,0 +1,129 @@
+use super::Face;
+
+struct Track {
+    id: usize,
+    face: Face,
+    // frames since last match
+    age: usize,
+}
+
+/// Greedy IoU matching of faces between consecutive frames
+pub struct Tracker {
+    tracks: Vec<Track>,
+    next_id: usize,
+    iou_threshold: f32,
+    max_age: usize,
+}
+
+impl Default for Tracker {
+    fn default() -> Self {
+        Self::new(0.3, 10)
+    }
+}
+
+impl Tracker {
+    pub fn new(iou_threshold: f32, max_age: usize) -> Self {
+        Self {
+            tracks: vec![],
+            next_id: 0,
+            iou_threshold,
+            max_age,
+        }
+    }
+
+    /// Sets track_id on faces (expects faces sorted by score like detection output)
+    pub fn update(&mut self, faces: &mut [Face]) {
+        let mut matched = vec![false; self.tracks.len()];
+
+        for face in faces.iter_mut() {
+            let best = self
+                .tracks
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| !matched[*idx])
+                .map(|(idx, track)| (idx, track.face.iou(face)))
+                .filter(|(_, iou)| *iou >= self.iou_threshold)
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+
+            let idx = match best {
+                Some((idx, _)) => idx,
+                None => {
+                    self.tracks.push(Track {
+                        id: self.next_id,
+                        face: face.clone(),
+                        age: 0,
+                    });
+                    matched.push(false);
+                    self.next_id += 1;
+                    self.tracks.len() - 1
+                }
+            };
+
+            matched[idx] = true;
+            let track = &mut self.tracks[idx];
+            face.track_id = Some(track.id);
+            track.face = face.clone();
+            track.age = 0;
+        }
+
+        for (idx, track) in self.tracks.iter_mut().enumerate() {
+            if !matched[idx] {
+                track.age += 1;
+            }
+        }
+        let max_age = self.max_age;
+        self.tracks.retain(|track| track.age <= max_age);
+    }
+
+    pub fn reset(&mut self) {
+        self.tracks.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Tracker;
+    use crate::model::data::{Face, KeyPoints};
+
+    fn face(bbox: (f32, f32, f32, f32)) -> Face {
+        Face {
+            score: 0.9,
+            keypoints: KeyPoints([[0.; 2]; 5]),
+            bbox,
+            track_id: None,
+        }
+    }
+
+    #[test]
+    fn keeps_track_id_for_moving_face() {
+        let mut tracker = Tracker::default();
+
+        let mut first = vec![face((0., 0., 100., 100.)), face((300., 300., 400., 400.))];
+        tracker.update(&mut first);
+        assert_eq!(first[0].track_id, Some(0));
+        assert_eq!(first[1].track_id, Some(1));
+
+        let mut second = vec![face((305., 302., 405., 402.)), face((5., 5., 105., 105.))];
+        tracker.update(&mut second);
+        assert_eq!(second[0].track_id, Some(1));
+        assert_eq!(second[1].track_id, Some(0));
+
+        let mut third = vec![face((600., 0., 700., 100.))];
+        tracker.update(&mut third);
+        assert_eq!(third[0].track_id, Some(2));
+    }
+
+    #[test]
+    fn drops_tracks_after_max_age() {
+        let mut tracker = Tracker::new(0.3, 1);
+
+        let mut faces = vec![face((0., 0., 100., 100.))];
+        tracker.update(&mut faces);
+        tracker.update(&mut []);
+        tracker.update(&mut []);
+
+        let mut faces = vec![face((0., 0., 100., 100.))];
+        tracker.update(&mut faces);
+        assert_eq!(faces[0].track_id, Some(1));
+    }
+}
@@ -167,9 +167,20 @@ impl Tensor {
     }
 
     pub fn transpose(
+        &mut self,
+        src: Tensor,
+        bbox: (usize, usize, usize, usize),
+    ) -> crate::Result<()> {
+        self.transpose_feathered(src, bbox, 0)
+    }
+
+    /// Like `transpose` but blends `feather` pixels from the bbox edges into
+    /// the target, 0 pastes a hard edge
+    pub fn transpose_feathered(
         &mut self,
         mut src: Tensor,
         bbox: (usize, usize, usize, usize),
+        feather: usize,
     ) -> crate::Result<()> {
         let (_, _, tar_y, tar_x) = self.dim();
         let (_, _, src_y, src_x) = src.dim();
@@ -187,7 +198,11 @@ impl Tensor {
             if (bbox.0 + x) > (tar_x - 1) || (bbox.1 + y) > (tar_y - 1) {
                 continue;
             }
-            self[(n, c, bbox.1 + y, bbox.0 + x)] = *v;
+            let edge = x.min(y).min(crop_x - 1 - x).min(crop_y - 1 - y);
+            let alpha = ((edge + 1) as f32 / (feather + 1) as f32).min(1.);
+
+            let tar = &mut self[(n, c, bbox.1 + y, bbox.0 + x)];
+            *tar = *v * alpha + *tar * (1. - alpha);
         }
 
         Ok(())
@@ -220,6 +235,29 @@ impl Tensor {
         }
         Ok(())
     }
+
+    /// Square marker centered at point (x, y)
+    pub fn mark(&mut self, point: (usize, usize), radius: usize) -> crate::Result<()> {
+        let (_, _, tar_y, tar_x) = self.dim();
+        if tar_x == 0 || tar_y == 0 || point.0 >= tar_x || point.1 >= tar_y {
+            return Ok(());
+        }
+
+        let mark_color = match self.normal {
+            super::Normal::N1ToP1 => [1., -1., -1.],
+            super::Normal::ZeroToP1 => [1., 0., 0.],
+            super::Normal::U8 => [255., 0., 0.],
+        };
+
+        for y in point.1.saturating_sub(radius)..=(point.1 + radius).min(tar_y - 1) {
+            for x in point.0.saturating_sub(radius)..=(point.0 + radius).min(tar_x - 1) {
+                for (c, color) in mark_color.iter().enumerate() {
+                    self[(0, c, y, x)] = *color;
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl From<TensorData> for Tensor {
@@ -345,6 +383,21 @@ mod test {
         );
     }
 
+    #[test]
+    fn feathers_pasted_edges() {
+        let mut hard = Tensor::new(Normal::ZeroToP1, TensorData::zeros((1, 3, 8, 8)));
+        let mut soft = hard.clone();
+        let patch = Tensor::new(Normal::ZeroToP1, TensorData::ones((1, 3, 4, 4)));
+
+        hard.transpose(patch.clone(), (2, 2, 6, 6)).unwrap();
+        soft.transpose_feathered(patch, (2, 2, 6, 6), 1).unwrap();
+
+        assert_eq!(hard[(0, 0, 2, 2)], 1.);
+        assert_eq!(soft[(0, 0, 2, 2)], 0.5);
+        assert_eq!(soft[(0, 0, 3, 3)], 1.);
+        assert_eq!(soft[(0, 0, 0, 0)], 0.);
+    }
+
     #[test]
     fn can_resize_tensor_data() {
         let mut rand = rand::thread_rng();
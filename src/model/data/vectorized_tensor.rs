@@ -1,6 +1,6 @@
 pub type VectorizedTensorArray = ndarray::Array<f32, ndarray::Dim<[usize; 2]>>;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct VectorizedTensor(pub VectorizedTensorArray);
 
 impl VectorizedTensor {
@@ -16,6 +16,16 @@ impl VectorizedTensor {
         let norm = self.norm();
         Self::from(self.0.dot(swap_graph) / norm)
     }
+
+    /// Average identity of unit length vectors, `None` when empty
+    pub fn mean(vectors: &[VectorizedTensor]) -> Option<Self> {
+        let first = vectors.first()?;
+        let sum = vectors.iter().fold(
+            VectorizedTensorArray::zeros(first.raw_dim()),
+            |accu, vector| accu + &vector.0 / vector.norm(),
+        );
+        Some(Self::from(sum / vectors.len() as f32))
+    }
 }
 
 impl From<VectorizedTensorArray> for VectorizedTensor {
@@ -13,9 +13,9 @@ type AnchorCenters = ndarray::Array<f32, ndarray::Dim<[usize; 2]>>;
 // 640 x 640 | threshold = 0.5 | fmc = 3
 pub struct DetectionModel {
     session: ort::Session,
-    threshold: f32,
+    pub(super) threshold: f32,
     // Non Maxium Suppression
-    nms_threshold: f32,
+    pub(super) nms_threshold: f32,
     input_size: (usize, usize),
     stride_fpn: Vec<usize>,
     anchor_map: HashMap<usize, AnchorCenters>,
@@ -24,7 +24,10 @@ pub struct DetectionModel {
 impl DetectionModel {
     // det_10g.onnx
     #[tracing::instrument(name = "Initialize detection model", err)]
-    pub fn new(onnx_path: std::path::PathBuf) -> Result<Self> {
+    pub fn new(
+        onnx_path: std::path::PathBuf,
+        config: &crate::setting::ModelConfig,
+    ) -> Result<Self> {
         let input_size = (640, 640);
         let stride_fpn = vec![8, 16, 32];
         let anchor_map =
@@ -45,10 +48,9 @@ impl DetectionModel {
         });
 
         Ok(Self {
-            session: super::start_session_from_file(onnx_path)?,
-            // get from config?
-            threshold: 0.5,
-            nms_threshold: 0.4,
+            session: super::start_session_from_file(onnx_path, config)?,
+            threshold: config.score_threshold,
+            nms_threshold: config.nms_threshold,
             stride_fpn,
             input_size,
             anchor_map: anchor_map.into_inner().map_err(Error::as_guard_error)?,
@@ -163,6 +165,7 @@ impl DetectionModel {
                             score: *score,
                             bbox: distance2bbox(idx, *stride, det_scale, anchor_centers, bboxes),
                             keypoints: distance2kps(idx, *stride, det_scale, anchor_centers, kpses),
+                            track_id: None,
                         })
                     })
                     .collect()
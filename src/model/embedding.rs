@@ -0,0 +1,316 @@
+//! Portable identity embedding file (`.emb`)
+//!
+//! All numbers are little endian.
+//!
+//! | offset     | size             | content                                    |
+//! |------------|------------------|--------------------------------------------|
+//! | 0          | 5                | magic `NFEMB`                              |
+//! | 5          | 1                | format version (`1`)                       |
+//! | 6          | 4                | header length `n` (u32)                    |
+//! | 10         | n                | utf-8 json header ([`EmbeddingMeta`])      |
+//! | 10 + n     | count * dim * 4  | raw recognition vectors (f32, row major)   |
+//!
+//! Vectors are the recognition model output before swap preparation, so they
+//! stay valid across swap model changes. `.npy` export writes the same vectors
+//! as a `(count, dim)` float32 array.
+
+use std::{io::Read, path::Path};
+
+use sha2::Digest;
+
+use crate::{Error, Result};
+
+use super::{data::VectorizedTensor, VECTORIZATION_DIM};
+
+const MAGIC: &[u8; 5] = b"NFEMB";
+const VERSION: u8 = 1;
+const PREFIX_LEN: usize = 10;
+pub const EMBEDDING_EXTENSION: &str = "emb";
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingNorm {
+    /// Raw model output
+    None,
+    /// Each vector scaled to unit length
+    L2,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingSource {
+    pub file: String,
+    pub sha256: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmbeddingMeta {
+    /// Recognition model name (ex: w600k_r50)
+    pub model: String,
+    pub dim: usize,
+    pub count: usize,
+    pub normalization: EmbeddingNorm,
+    pub sources: Vec<EmbeddingSource>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Embedding {
+    pub meta: EmbeddingMeta,
+    /// (1, dim) each
+    pub vectors: Vec<VectorizedTensor>,
+}
+
+impl Embedding {
+    pub fn new(model: impl Into<String>, dim: usize, normalization: EmbeddingNorm) -> Self {
+        Self {
+            meta: EmbeddingMeta {
+                model: model.into(),
+                dim,
+                count: 0,
+                normalization,
+                sources: vec![],
+            },
+            vectors: vec![],
+        }
+    }
+
+    pub fn is_embedding_file(path: &Path) -> bool {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(EMBEDDING_EXTENSION))
+    }
+
+    pub fn push(&mut self, vector: VectorizedTensor, source: EmbeddingSource) -> Result<()> {
+        if vector.len() != self.meta.dim {
+            return Err(Error::InvalidModelIOError(format!(
+                "Embedding dimension mismatch: expected {} got {}",
+                self.meta.dim,
+                vector.len()
+            )));
+        }
+
+        let vector = match self.meta.normalization {
+            EmbeddingNorm::None => vector,
+            EmbeddingNorm::L2 => VectorizedTensor::from(&vector.0 / vector.norm()),
+        };
+        self.vectors.push(vector);
+        self.meta.sources.push(source);
+        self.meta.count = self.vectors.len();
+        Ok(())
+    }
+
+    /// Average identity of unit length vectors
+    pub fn mean(&self) -> Result<VectorizedTensor> {
+        VectorizedTensor::mean(&self.vectors)
+            .ok_or_else(|| Error::InvalidModelIOError("Embedding has no vectors".into()))
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let header = serde_json::to_vec(&self.meta).map_err(Error::as_unknown_error)?;
+        let mut bytes = Vec::with_capacity(PREFIX_LEN + header.len() + self.data_len());
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&header);
+        self.extend_data(&mut bytes);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let invalid = |reason: &str| {
+            Error::InvalidModelIOError(format!("Invalid embedding file: {}", reason))
+        };
+
+        if bytes.len() < PREFIX_LEN || &bytes[..5] != MAGIC {
+            return Err(invalid("missing magic bytes"));
+        }
+        if bytes[5] != VERSION {
+            return Err(invalid(&format!("unsupported version {}", bytes[5])));
+        }
+
+        let header_len = u32::from_le_bytes([bytes[6], bytes[7], bytes[8], bytes[9]]) as usize;
+        let header = bytes
+            .get(PREFIX_LEN..PREFIX_LEN + header_len)
+            .ok_or_else(|| invalid("truncated header"))?;
+        let meta = serde_json::from_slice::<EmbeddingMeta>(header)
+            .map_err(|err| invalid(&err.to_string()))?;
+
+        if meta.dim != VECTORIZATION_DIM {
+            return Err(invalid(&format!(
+                "expected {} dimensions got {}",
+                VECTORIZATION_DIM, meta.dim
+            )));
+        }
+        let data_len = meta
+            .count
+            .checked_mul(meta.dim)
+            .and_then(|len| len.checked_mul(4))
+            .ok_or_else(|| invalid("vector count too large"))?;
+        let data = &bytes[PREFIX_LEN + header_len..];
+        if data.len() != data_len {
+            return Err(invalid("vector data length doesn't match header"));
+        }
+
+        let dim = meta.dim;
+        Ok(Self {
+            vectors: data
+                .chunks_exact(dim * 4)
+                .map(|chunk| {
+                    VectorizedTensor::from(ndarray::Array::from_shape_fn((1, dim), |(_, i)| {
+                        f32::from_le_bytes([
+                            chunk[i * 4],
+                            chunk[i * 4 + 1],
+                            chunk[i * 4 + 2],
+                            chunk[i * 4 + 3],
+                        ])
+                    }))
+                })
+                .collect(),
+            meta,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Self::from_bytes(&std::fs::read(path).map_err(Error::as_unknown_error)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_bytes()?).map_err(Error::as_unknown_error)
+    }
+
+    /// NumPy v1.0 `.npy` with `(count, dim)` float32 array
+    pub fn to_npy(&self) -> Vec<u8> {
+        let dict = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+            self.vectors.len(),
+            self.meta.dim
+        );
+        // magic(6) + version(2) + header len(2) + dict + '\n' padded to 64 bytes
+        let padding = (64 - (10 + dict.len() + 1) % 64) % 64;
+        let header = format!("{}{}\n", dict, " ".repeat(padding));
+
+        let mut bytes = Vec::with_capacity(10 + header.len() + self.data_len());
+        bytes.extend_from_slice(b"\x93NUMPY");
+        bytes.extend_from_slice(&[1, 0]);
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        self.extend_data(&mut bytes);
+        bytes
+    }
+
+    pub fn save_npy(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, self.to_npy()).map_err(Error::as_unknown_error)
+    }
+
+    fn data_len(&self) -> usize {
+        self.vectors.len() * self.meta.dim * 4
+    }
+
+    fn extend_data(&self, bytes: &mut Vec<u8>) {
+        for vector in &self.vectors {
+            for v in vector.iter() {
+                bytes.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+    }
+}
+
+impl EmbeddingSource {
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let mut file = std::fs::File::open(path).map_err(Error::as_unknown_error)?;
+        let mut hasher = sha2::Sha256::new();
+        let mut buffer = [0u8; 8192];
+        loop {
+            let read = file.read(&mut buffer).map_err(Error::as_unknown_error)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+
+        Ok(Self {
+            file: path.display().to_string(),
+            sha256: hasher
+                .finalize()
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::Rng;
+
+    use super::{Embedding, EmbeddingNorm, EmbeddingSource};
+    use crate::model::data::VectorizedTensor;
+
+    fn random_embedding(count: usize) -> Embedding {
+        let mut rand = rand::thread_rng();
+        let mut embedding = Embedding::new("w600k_r50", 512, EmbeddingNorm::None);
+        for idx in 0..count {
+            embedding
+                .push(
+                    VectorizedTensor::from(ndarray::Array::from_shape_fn((1, 512), |_| {
+                        rand.gen::<f32>() * 2. - 1.
+                    })),
+                    EmbeddingSource {
+                        file: format!("face_{}.jpg", idx),
+                        sha256: "0".repeat(64),
+                    },
+                )
+                .expect("Failed to push vector");
+        }
+        embedding
+    }
+
+    #[test]
+    fn can_round_trip_embedding_bytes() {
+        let embedding = random_embedding(3);
+        let decoded = Embedding::from_bytes(&embedding.to_bytes().expect("Failed to encode"))
+            .expect("Failed to decode");
+
+        assert_eq!(decoded.meta, embedding.meta);
+        assert_eq!(decoded.meta.count, 3);
+        for (a, b) in decoded.vectors.iter().zip(embedding.vectors.iter()) {
+            assert_eq!(a.0, b.0);
+        }
+
+        let mut corrupted = embedding.to_bytes().expect("Failed to encode");
+        corrupted.pop();
+        assert!(Embedding::from_bytes(&corrupted).is_err());
+        assert!(Embedding::from_bytes(b"NOTEMB0000").is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_headers() {
+        let with_meta = |dim: usize, count: usize| {
+            let mut embedding = Embedding::new("w600k_r50", dim, EmbeddingNorm::None);
+            embedding.meta.count = count;
+            embedding.to_bytes().expect("Failed to encode")
+        };
+
+        // count * dim * 4 wraps to 0 without overflow checks
+        assert!(Embedding::from_bytes(&with_meta(512, usize::MAX / 4 + 1)).is_err());
+        assert!(Embedding::from_bytes(&with_meta(usize::MAX, 1)).is_err());
+        assert!(Embedding::from_bytes(&with_meta(0, 0)).is_err());
+    }
+
+    #[test]
+    fn writes_aligned_npy_header() {
+        let npy = random_embedding(2).to_npy();
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+
+        assert_eq!(&npy[..6], b"\x93NUMPY");
+        assert_eq!((10 + header_len) % 64, 0);
+        assert_eq!(npy.len(), 10 + header_len + 2 * 512 * 4);
+        assert!(String::from_utf8_lossy(&npy[10..10 + header_len]).contains("'shape': (2, 512)"));
+    }
+
+    #[test]
+    fn mean_is_unit_scaled_average() {
+        let embedding = random_embedding(1);
+        let mean = embedding.mean().expect("Failed to average");
+        assert!((mean.norm() - 1.).abs() < 1e-4);
+    }
+}
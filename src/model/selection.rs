@@ -0,0 +1,150 @@
+use std::str::FromStr;
+
+use crate::{Error, Result};
+
+use super::{
+    data::{Face, VectorizedTensor},
+    Tensor,
+};
+
+/// Which detected faces of a source image make up the identity
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum FaceSelection {
+    /// Highest detection score
+    #[default]
+    Best,
+    /// Largest bounding box
+    Largest,
+    /// Detection index, faces are ordered by score
+    Index(usize),
+    /// Average of several detections
+    Average(Vec<usize>),
+    /// Average of every detected face
+    All,
+}
+
+impl FaceSelection {
+    /// Indices into `faces`, errors when none match
+    pub fn resolve(&self, faces: &[Face]) -> Result<Vec<usize>> {
+        if faces.is_empty() {
+            return Err(Error::InvalidModelIOError("No Face detected".into()));
+        }
+        let out_of_range = |idx: usize| {
+            Error::InvalidModelIOError(format!(
+                "Face index {} out of range, {} detected",
+                idx,
+                faces.len()
+            ))
+        };
+        let max_by = |key: fn(&Face) -> f32| {
+            (0..faces.len())
+                .max_by(|a, b| key(&faces[*a]).total_cmp(&key(&faces[*b])))
+                .unwrap_or_default()
+        };
+
+        match self {
+            FaceSelection::Best => Ok(vec![max_by(|face| face.score)]),
+            FaceSelection::Largest => Ok(vec![max_by(|face| {
+                (face.bbox.2 - face.bbox.0) * (face.bbox.3 - face.bbox.1)
+            })]),
+            FaceSelection::Index(idx) if *idx < faces.len() => Ok(vec![*idx]),
+            FaceSelection::Index(idx) => Err(out_of_range(*idx)),
+            FaceSelection::Average(indices) => {
+                if indices.is_empty() {
+                    return Err(Error::InvalidModelIOError("No face selected".into()));
+                }
+                let mut resolved = vec![];
+                for idx in indices {
+                    if *idx >= faces.len() {
+                        return Err(out_of_range(*idx));
+                    }
+                    if !resolved.contains(idx) {
+                        resolved.push(*idx);
+                    }
+                }
+                Ok(resolved)
+            }
+            FaceSelection::All => Ok((0..faces.len()).collect()),
+        }
+    }
+}
+
+/// `best`, `largest`, `all`, an index (`2`) or indices to average (`0,2`)
+impl FromStr for FaceSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "best" => Ok(FaceSelection::Best),
+            "largest" => Ok(FaceSelection::Largest),
+            "all" => Ok(FaceSelection::All),
+            list if list.contains(',') => list
+                .split(',')
+                .map(|idx| idx.trim().parse::<usize>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map(FaceSelection::Average)
+                .map_err(|_| format!("Invalid face indices: {}", s)),
+            idx => idx.parse().map(FaceSelection::Index).map_err(|_| {
+                format!(
+                    "Invalid face selection: {} (best, largest, all, index or 0,2)",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+/// Detected source face with its aligned crop and raw recognition vector
+#[derive(Debug, Clone)]
+pub struct SourceFace {
+    pub face: Face,
+    pub tensor: Tensor,
+    pub raw: VectorizedTensor,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::model::data::{Face, KeyPoints};
+
+    use super::FaceSelection;
+
+    fn face(score: f32, size: f32) -> Face {
+        Face {
+            score,
+            keypoints: KeyPoints([[0.; 2]; 5]),
+            bbox: (0., 0., size, size),
+            track_id: None,
+        }
+    }
+
+    #[test]
+    fn resolves_selection_policies() {
+        let faces = [face(0.9, 10.), face(0.7, 40.), face(0.6, 20.)];
+
+        assert_eq!(FaceSelection::Best.resolve(&faces).unwrap(), vec![0]);
+        assert_eq!(FaceSelection::Largest.resolve(&faces).unwrap(), vec![1]);
+        assert_eq!(FaceSelection::Index(2).resolve(&faces).unwrap(), vec![2]);
+        assert_eq!(
+            FaceSelection::Average(vec![2, 0, 2])
+                .resolve(&faces)
+                .unwrap(),
+            vec![2, 0]
+        );
+        assert_eq!(FaceSelection::All.resolve(&faces).unwrap(), vec![0, 1, 2]);
+
+        assert!(FaceSelection::Index(3).resolve(&faces).is_err());
+        assert!(FaceSelection::Average(vec![]).resolve(&faces).is_err());
+        assert!(FaceSelection::Best.resolve(&[]).is_err());
+    }
+
+    #[test]
+    fn parses_selection() {
+        assert_eq!("best".parse(), Ok(FaceSelection::Best));
+        assert_eq!("Largest".parse(), Ok(FaceSelection::Largest));
+        assert_eq!("all".parse(), Ok(FaceSelection::All));
+        assert_eq!("1".parse(), Ok(FaceSelection::Index(1)));
+        assert_eq!("0, 2".parse(), Ok(FaceSelection::Average(vec![0, 2])));
+        assert!("left".parse::<FaceSelection>().is_err());
+        assert!("0,x".parse::<FaceSelection>().is_err());
+    }
+}
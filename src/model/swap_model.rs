@@ -19,11 +19,14 @@ pub struct SwapModel {
 impl SwapModel {
     // inswapper_128.onnx
     #[tracing::instrument(name = "Initialize swap model", err)]
-    pub fn new(onnx_path: std::path::PathBuf) -> Result<Self> {
+    pub fn new(
+        onnx_path: std::path::PathBuf,
+        config: &crate::setting::ModelConfig,
+    ) -> Result<Self> {
         Ok(Self {
             input_size: (128, 128),
             input_size_mat: InputSizeMatrix::from_shape_fn((1, 3, 128, 128), |d| d),
-            session: super::start_session_from_file(onnx_path)?,
+            session: super::start_session_from_file(onnx_path, config)?,
             graph: InitialGraphOutput::get()?,
         })
     }
@@ -0,0 +1,176 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// Frame pipeline steps, `Display` is measured by the gui
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Detect,
+    /// Face crop before swapping or recognition
+    Align,
+    /// Recognition and source preparation
+    Embed,
+    Swap,
+    /// Blending the swapped crop back into the frame
+    Paste,
+    /// Frame handed to the gui texture
+    Display,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 6] = [
+        Stage::Detect,
+        Stage::Align,
+        Stage::Embed,
+        Stage::Swap,
+        Stage::Paste,
+        Stage::Display,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stage::Detect => "detect",
+            Stage::Align => "align",
+            Stage::Embed => "embed",
+            Stage::Swap => "swap",
+            Stage::Paste => "paste",
+            Stage::Display => "display",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+/// Time spent per stage, summed over every face of a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StageTimings([Duration; 6]);
+
+impl StageTimings {
+    pub fn add(&mut self, stage: Stage, duration: Duration) {
+        self.0[stage.index()] += duration;
+    }
+
+    pub fn get(&self, stage: Stage) -> Duration {
+        self.0[stage.index()]
+    }
+
+    pub fn total(&self) -> Duration {
+        self.0.iter().sum()
+    }
+}
+
+/// Runs `f` in a `stage` span (debug level) and adds its time to `timings`
+pub fn measure<T>(timings: &mut StageTimings, stage: Stage, f: impl FnOnce() -> T) -> T {
+    let span = tracing::debug_span!("stage", name = stage.name());
+    let _enter = span.enter();
+    let start_inst = Instant::now();
+    let output = f();
+    timings.add(stage, start_inst.elapsed());
+    output
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StageStats {
+    pub current: Duration,
+    pub avg: Duration,
+    pub p95: Duration,
+}
+
+/// Rolling window of per frame timings
+#[derive(Debug, Clone)]
+pub struct TimingHistory {
+    frames: VecDeque<StageTimings>,
+    capacity: usize,
+}
+
+impl Default for TimingHistory {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
+
+impl TimingHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&mut self, timings: StageTimings) {
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(timings);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Oldest first
+    pub fn samples(&self, stage: Stage) -> impl Iterator<Item = Duration> + '_ {
+        self.frames.iter().map(move |timings| timings.get(stage))
+    }
+
+    pub fn stats(&self, stage: Stage) -> StageStats {
+        let mut samples = self.samples(stage).collect::<Vec<_>>();
+        let Some(current) = samples.last().copied() else {
+            return StageStats::default();
+        };
+        let avg = samples.iter().sum::<Duration>() / samples.len() as u32;
+        samples.sort();
+        // nearest rank
+        let rank = (samples.len() as f32 * 0.95).ceil() as usize;
+        StageStats {
+            current,
+            avg,
+            p95: samples[rank.clamp(1, samples.len()) - 1],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::{measure, Stage, StageTimings, TimingHistory};
+
+    fn frame(detect_ms: u64, swap_ms: u64) -> StageTimings {
+        let mut timings = StageTimings::default();
+        timings.add(Stage::Detect, Duration::from_millis(detect_ms));
+        timings.add(Stage::Swap, Duration::from_millis(swap_ms));
+        timings
+    }
+
+    #[test]
+    fn sums_stage_time_per_frame() {
+        let mut timings = frame(4, 10);
+        timings.add(Stage::Swap, Duration::from_millis(6));
+        let value = measure(&mut timings, Stage::Paste, || 7);
+
+        assert_eq!(value, 7);
+        assert_eq!(timings.get(Stage::Swap), Duration::from_millis(16));
+        assert!(timings.total() >= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn computes_rolling_stats() {
+        let mut history = TimingHistory::new(20);
+        assert_eq!(history.stats(Stage::Detect).p95, Duration::ZERO);
+
+        // 25 frames, the first 5 fall out of the window
+        for ms in 1..=25 {
+            history.push(frame(ms, 0));
+        }
+        let stats = history.stats(Stage::Detect);
+        assert_eq!(stats.current, Duration::from_millis(25));
+        assert_eq!(stats.avg, Duration::from_micros(15_500));
+        assert_eq!(stats.p95, Duration::from_millis(24));
+        assert_eq!(history.samples(Stage::Detect).count(), 20);
+        assert_eq!(history.stats(Stage::Swap).avg, Duration::ZERO);
+    }
+}
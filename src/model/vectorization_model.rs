@@ -14,11 +14,14 @@ pub struct VectorizationModel {
 impl VectorizationModel {
     // w600k_r50.onnx
     #[tracing::instrument(name = "Initialize recognition model", err)]
-    pub fn new(onnx_path: std::path::PathBuf) -> Result<Self> {
+    pub fn new(
+        onnx_path: std::path::PathBuf,
+        config: &crate::setting::ModelConfig,
+    ) -> Result<Self> {
         Ok(Self {
             input_size: (112, 112),
             input_size_mat: InputSizeMatrix::from_shape_fn((1, 3, 112, 112), |d| d),
-            session: super::start_session_from_file(onnx_path)?,
+            session: super::start_session_from_file(onnx_path, config)?,
         })
     }
 
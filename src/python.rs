@@ -0,0 +1,213 @@
+//! Python module (`python` feature), built with maturin (see pyproject.toml)
+//!
+//! ```python
+//! import noface
+//!
+//! swapper = noface.Swapper("models")
+//! faces = swapper.detect(frame)          # frame: (h, w, 3) uint8 rgb
+//! vector = swapper.embed(face_image)     # (512,) float32
+//! group = swapper.embed(photo, face=[0, 2])  # or "best", "largest", "all", 1
+//! swapped = swapper.swap(frame, vector)  # or set_source(face_image) once
+//! ```
+//!
+//! Inference runs with the GIL released.
+
+use std::path::PathBuf;
+
+use numpy::{
+    IntoPyArray, PyArray1, PyArray3, PyReadonlyArray1, PyReadonlyArray3, PyUntypedArrayMethods,
+};
+use pyo3::{
+    exceptions::{PyRuntimeError, PyValueError},
+    prelude::*,
+};
+
+use crate::{
+    image::Image,
+    model::{
+        data::{Face, VectorizedTensor},
+        FaceSelection,
+    },
+    swapper::Swapper,
+    Error,
+};
+
+fn to_py_err(err: Error) -> PyErr {
+    match err {
+        Error::ImageError(_) | Error::InvalidModelIOError(_) => {
+            PyValueError::new_err(err.to_string())
+        }
+        _ => PyRuntimeError::new_err(err.to_string()),
+    }
+}
+
+/// (h, w, 3) uint8 rgb array copied into an image
+fn to_image(array: &PyReadonlyArray3<u8>) -> PyResult<Image> {
+    let &[height, width, 3] = array.shape() else {
+        return Err(PyValueError::new_err(format!(
+            "Expected (h, w, 3) rgb array, got {:?}",
+            array.shape()
+        )));
+    };
+
+    let array = array.as_array();
+    Ok(Image::from(image::RgbImage::from_fn(
+        width as u32,
+        height as u32,
+        |x, y| {
+            let (x, y) = (x as usize, y as usize);
+            image::Rgb([array[[y, x, 0]], array[[y, x, 1]], array[[y, x, 2]]])
+        },
+    )))
+}
+
+/// `face` argument: an index, indices to average or a policy name
+#[derive(FromPyObject)]
+enum PySelection {
+    Index(usize),
+    Indices(Vec<usize>),
+    Policy(String),
+}
+
+fn to_selection(face: Option<PySelection>) -> PyResult<FaceSelection> {
+    match face {
+        None => Ok(FaceSelection::default()),
+        Some(PySelection::Index(idx)) => Ok(FaceSelection::Index(idx)),
+        Some(PySelection::Indices(indices)) => Ok(FaceSelection::Average(indices)),
+        Some(PySelection::Policy(policy)) => policy.parse().map_err(PyValueError::new_err),
+    }
+}
+
+#[pyclass(name = "Face", module = "noface", frozen, get_all)]
+#[derive(Clone)]
+pub struct PyFace {
+    score: f32,
+    /// (x1, y1, x2, y2)
+    bbox: (f32, f32, f32, f32),
+    /// Eyes, nose and mouth corners as (x, y)
+    keypoints: Vec<(f32, f32)>,
+    track_id: Option<usize>,
+}
+
+impl From<Face> for PyFace {
+    fn from(face: Face) -> Self {
+        Self {
+            score: face.score,
+            bbox: face.bbox,
+            keypoints: face.keypoints.iter().map(|[x, y]| (*x, *y)).collect(),
+            track_id: face.track_id,
+        }
+    }
+}
+
+#[pymethods]
+impl PyFace {
+    fn __repr__(&self) -> String {
+        format!("Face(score={:.3}, bbox={:?})", self.score, self.bbox)
+    }
+}
+
+#[pyclass(name = "Swapper", module = "noface", frozen)]
+pub struct PySwapper(Swapper);
+
+#[pymethods]
+impl PySwapper {
+    #[new]
+    #[pyo3(signature = (models_dir = None, cuda = false))]
+    fn new(py: Python<'_>, models_dir: Option<PathBuf>, cuda: bool) -> PyResult<Self> {
+        py.allow_threads(|| {
+            let mut builder = Swapper::builder().cuda(cuda);
+            if let Some(dir) = models_dir {
+                builder = builder.models(dir);
+            }
+            builder.build()
+        })
+        .map(Self)
+        .map_err(to_py_err)
+    }
+
+    /// Faces sorted by score
+    fn detect(&self, py: Python<'_>, image: PyReadonlyArray3<u8>) -> PyResult<Vec<PyFace>> {
+        let image = to_image(&image)?;
+        let faces = py
+            .allow_threads(|| self.0.detect(&image))
+            .map_err(to_py_err)?;
+        Ok(faces.into_iter().map(PyFace::from).collect())
+    }
+
+    /// Raw recognition vector of the best face, `face` picks others
+    #[pyo3(signature = (image, face = None))]
+    fn embed<'py>(
+        &self,
+        py: Python<'py>,
+        image: PyReadonlyArray3<u8>,
+        face: Option<PySelection>,
+    ) -> PyResult<Bound<'py, PyArray1<f32>>> {
+        let image = to_image(&image)?;
+        let selection = to_selection(face)?;
+        let vector = py
+            .allow_threads(|| self.0.embed_with(&image, &selection))
+            .map_err(to_py_err)?;
+        Ok(vector
+            .iter()
+            .copied()
+            .collect::<Vec<f32>>()
+            .into_pyarray_bound(py))
+    }
+
+    /// Source used by `swap` when no vector is given
+    #[pyo3(signature = (image, face = None))]
+    fn set_source(
+        &self,
+        py: Python<'_>,
+        image: PyReadonlyArray3<u8>,
+        face: Option<PySelection>,
+    ) -> PyResult<()> {
+        let image = to_image(&image)?;
+        let selection = to_selection(face)?;
+        py.allow_threads(|| {
+            let identity = self.0.identity_from_image_with(&image, &selection)?;
+            self.0.set_identity(identity)
+        })
+        .map_err(to_py_err)
+    }
+
+    /// Swaps the first face, returns a new (h, w, 3) uint8 array
+    #[pyo3(signature = (image, source = None))]
+    fn swap<'py>(
+        &self,
+        py: Python<'py>,
+        image: PyReadonlyArray3<u8>,
+        source: Option<PyReadonlyArray1<f32>>,
+    ) -> PyResult<Bound<'py, PyArray3<u8>>> {
+        let image = to_image(&image)?;
+        let source = source.map(|source| {
+            let source = source.as_array();
+            VectorizedTensor::from(source.to_owned().insert_axis(ndarray::Axis(0)))
+        });
+
+        let output = py
+            .allow_threads(|| match source {
+                Some(source) => self
+                    .0
+                    .swap_with(&image, &self.0.identity_from_vector(&source)?),
+                None => self.0.swap(&image),
+            })
+            .map_err(to_py_err)?;
+
+        let (width, height) = output.dimensions();
+        let array = ndarray::Array3::from_shape_vec(
+            (height as usize, width as usize, 3),
+            output.0.into_raw(),
+        )
+        .map_err(|err| PyRuntimeError::new_err(err.to_string()))?;
+        Ok(array.into_pyarray_bound(py))
+    }
+}
+
+#[pymodule]
+fn noface(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySwapper>()?;
+    m.add_class::<PyFace>()?;
+    Ok(())
+}
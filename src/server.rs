@@ -0,0 +1,458 @@
+//! Local http api
+//!
+//! - `GET /healthz`: `200` while the process is up
+//! - `GET /readyz`: `200` once models are loaded and the job queue has room
+//! - `GET /embeddings`: ids of `.emb` files in the embedding store
+//! - `POST /swap`: multipart `target` image with optional `source` image or
+//!   `embedding` id, responds with the swapped png
+//! - `POST /detect`: encoded image body, responds with faces json
+//! - `GET /stream`: websocket of live frames, see [`stream`]
+//!
+//! Swap falls back to the server default source when the request has none.
+//! Errors are returned as `{"error": "..."}`.
+
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Multipart, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{
+    image::Image,
+    model::{data::Face, Embedding},
+    setting::ModelConfig,
+    swapper::{Identity, Swapper},
+    Error, Result,
+};
+
+pub use embeddings::EmbeddingStore;
+
+pub mod embeddings;
+pub mod stream;
+
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    pub addr: SocketAddr,
+    pub model: ModelConfig,
+    /// Max request body size in bytes
+    pub body_limit: usize,
+    /// Max jobs running or waiting for the model, later requests get `503`
+    pub queue: usize,
+    pub embeddings: PathBuf,
+    /// Default swap source (face image, `.emb` file or stored embedding id)
+    pub source: Option<PathBuf>,
+}
+
+pub(crate) struct ServerState {
+    swapper: OnceLock<Swapper>,
+    queue: Arc<Semaphore>,
+    embeddings: EmbeddingStore,
+    body_limit: usize,
+}
+
+type AppState = Arc<ServerState>;
+
+pub(crate) struct ApiError(StatusCode, String);
+
+#[derive(serde::Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl ApiError {
+    pub fn bad_request(msg: impl ToString) -> Self {
+        Self(StatusCode::BAD_REQUEST, msg.to_string())
+    }
+}
+
+impl From<Error> for ApiError {
+    fn from(err: Error) -> Self {
+        let status = match err {
+            Error::ImageError(_) => StatusCode::BAD_REQUEST,
+            Error::InvalidModelIOError(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+impl ServerState {
+    fn swapper(&self) -> std::result::Result<&Swapper, ApiError> {
+        self.swapper.get().ok_or(ApiError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Models are still loading".into(),
+        ))
+    }
+
+    fn reserve(&self) -> std::result::Result<OwnedSemaphorePermit, ApiError> {
+        self.queue
+            .clone()
+            .try_acquire_owned()
+            .map_err(|_| ApiError(StatusCode::SERVICE_UNAVAILABLE, "Job queue is full".into()))
+    }
+
+    /// Waits for a queue slot instead of rejecting
+    async fn wait_slot(&self) -> std::result::Result<OwnedSemaphorePermit, ApiError> {
+        self.queue
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))
+    }
+}
+
+/// Runs `job` on the blocking pool when a queue slot is free, `503` otherwise
+async fn run_job<T, F>(state: &AppState, job: F) -> std::result::Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&Swapper) -> Result<T> + Send + 'static,
+{
+    let permit = state.reserve()?;
+    state.swapper()?;
+    spawn_job(state, permit, job).await
+}
+
+/// Runs `job` on the blocking pool, the queue slot is released when it ends
+async fn spawn_job<T, F>(
+    state: &AppState,
+    permit: OwnedSemaphorePermit,
+    job: F,
+) -> std::result::Result<T, ApiError>
+where
+    T: Send + 'static,
+    F: FnOnce(&Swapper) -> Result<T> + Send + 'static,
+{
+    let state = state.clone();
+
+    tokio::task::spawn_blocking(move || {
+        let _permit = permit;
+        job(state.swapper()?).map_err(ApiError::from)
+    })
+    .await
+    .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+}
+
+/// Reads a stored embedding on the blocking pool
+async fn load_embedding(state: &AppState, id: String) -> std::result::Result<Embedding, ApiError> {
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || state.embeddings.load(&id).map_err(ApiError::from))
+        .await
+        .map_err(|err| ApiError(StatusCode::INTERNAL_SERVER_ERROR, err.to_string()))?
+}
+
+/// Serves until ctrl-c, models are loaded in background after binding
+#[tracing::instrument(name = "Running server", err)]
+pub async fn serve(config: ServerConfig) -> Result<()> {
+    let state = Arc::new(ServerState {
+        swapper: OnceLock::new(),
+        queue: Arc::new(Semaphore::new(config.queue.max(1))),
+        embeddings: EmbeddingStore::new(config.embeddings.clone()),
+        body_limit: config.body_limit,
+    });
+
+    let listener = tokio::net::TcpListener::bind(config.addr)
+        .await
+        .map_err(Error::as_unknown_error)?;
+    tracing::info!("Listening on http://{}", config.addr);
+
+    let (fail_tx, fail_rx) = tokio::sync::oneshot::channel::<Error>();
+    let (loader, model, source) = (state.clone(), config.model.clone(), config.source.clone());
+    tokio::task::spawn_blocking(move || {
+        let load = || -> Result<()> {
+            let swapper = Swapper::builder().config(model).build()?;
+            if let Some(source) = &source {
+                swapper.set_identity(load_identity(&swapper, &loader.embeddings, source)?)?;
+            }
+            let _ = loader.swapper.set(swapper);
+            tracing::info!("Models loaded");
+            Ok(())
+        };
+        if let Err(err) = load() {
+            let _ = fail_tx.send(err);
+        }
+    });
+
+    let failure = Arc::new(Mutex::new(None));
+    let shutdown = {
+        let failure = failure.clone();
+        async move {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                Ok(err) = fail_rx => {
+                    if let Ok(mut failure) = failure.lock() {
+                        *failure = Some(err);
+                    }
+                }
+            }
+        }
+    };
+
+    axum::serve(listener, router(state))
+        .with_graceful_shutdown(shutdown)
+        .await
+        .map_err(Error::as_unknown_error)?;
+
+    let failure = failure.lock().map_err(Error::as_guard_error)?.take();
+    match failure {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/healthz", get(|| async { StatusCode::OK }))
+        .route("/readyz", get(readyz))
+        .route("/embeddings", get(list_embeddings))
+        .route("/swap", post(swap))
+        .route("/detect", post(detect))
+        .route("/stream", get(stream::stream))
+        .layer(DefaultBodyLimit::max(state.body_limit))
+        .with_state(state)
+}
+
+/// Face image or `.emb` path, embedding ids are resolved against the store
+fn load_identity(swapper: &Swapper, store: &EmbeddingStore, source: &Path) -> Result<Identity> {
+    if Embedding::is_embedding_file(source) {
+        return swapper.identity_from_embedding(&Embedding::load(source)?);
+    }
+    if !source.exists() {
+        if let Some(id) = source.to_str() {
+            return swapper.identity_from_embedding(&store.load(id)?);
+        }
+    }
+    swapper.identity_from_image(&Image::from_path(source.to_path_buf(), None)?)
+}
+
+async fn readyz(State(state): State<AppState>) -> std::result::Result<StatusCode, ApiError> {
+    state.swapper()?;
+    if state.queue.available_permits() == 0 {
+        return Err(ApiError(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Job queue is full".into(),
+        ));
+    }
+    Ok(StatusCode::OK)
+}
+
+async fn list_embeddings(
+    State(state): State<AppState>,
+) -> std::result::Result<Json<Vec<String>>, ApiError> {
+    Ok(Json(state.embeddings.list()?))
+}
+
+async fn swap(
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> std::result::Result<Response, ApiError> {
+    let (mut target, mut source, mut embedding) = (None, None, None);
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(ApiError::bad_request)?
+    {
+        let name = field.name().map(str::to_owned);
+        let bytes = field.bytes().await.map_err(ApiError::bad_request)?;
+        match name.as_deref() {
+            Some("target") => target = Some(bytes),
+            Some("source") => source = Some(bytes),
+            Some("embedding") => {
+                embedding = Some(String::from_utf8(bytes.to_vec()).map_err(ApiError::bad_request)?)
+            }
+            _ => {}
+        }
+    }
+    let Some(target) = target else {
+        return Err(ApiError::bad_request("Missing multipart field `target`"));
+    };
+
+    let embedding = match embedding {
+        Some(id) => Some(load_embedding(&state, id.trim().to_owned()).await?),
+        None => None,
+    };
+
+    let png = run_job(&state, move |swapper| {
+        let identity = match (source, embedding) {
+            (Some(source), _) => Some(swapper.identity_from_image(&Image::from_bytes(&source)?)?),
+            (None, Some(embedding)) => Some(swapper.identity_from_embedding(&embedding)?),
+            (None, None) => None,
+        };
+
+        let target = Image::from_bytes(&target)?;
+        let output = match identity {
+            Some(identity) => swapper.swap_with(&target, &identity)?,
+            None => swapper.swap(&target)?,
+        };
+        output.encode(image::ImageFormat::Png)
+    })
+    .await?;
+
+    Ok(([(header::CONTENT_TYPE, "image/png")], png).into_response())
+}
+
+async fn detect(
+    State(state): State<AppState>,
+    body: Bytes,
+) -> std::result::Result<Json<Vec<Face>>, ApiError> {
+    let faces = run_job(&state, move |swapper| {
+        swapper.detect(&Image::from_bytes(&body)?)
+    })
+    .await?;
+    Ok(Json(faces))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::{Arc, OnceLock};
+
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+    };
+    use tokio::sync::Semaphore;
+    use tower::ServiceExt;
+
+    use super::{router, AppState, EmbeddingStore, ServerState};
+
+    const BOUNDARY: &str = "noface-test-boundary";
+
+    /// Models are never loaded, routes needing them answer `503`
+    fn state(queue: usize) -> AppState {
+        Arc::new(ServerState {
+            swapper: OnceLock::new(),
+            queue: Arc::new(Semaphore::new(queue)),
+            embeddings: EmbeddingStore::new(std::env::temp_dir().join("noface_missing_store")),
+            body_limit: 1 << 20,
+        })
+    }
+
+    async fn call(state: &AppState, request: Request<Body>) -> (StatusCode, String) {
+        let response = router(state.clone())
+            .oneshot(request)
+            .await
+            .expect("Failed calling router");
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed reading body");
+        (status, String::from_utf8_lossy(&body).into_owned())
+    }
+
+    fn multipart(fields: &[(&str, &[u8])]) -> Request<Body> {
+        let mut body = vec![];
+        for (name, value) in fields {
+            body.extend_from_slice(
+                format!(
+                    "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                    BOUNDARY, name
+                )
+                .as_bytes(),
+            );
+            body.extend_from_slice(value);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", BOUNDARY).as_bytes());
+        Request::post("/swap")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from(body))
+            .expect("Failed building request")
+    }
+
+    fn get(uri: &str) -> Request<Body> {
+        Request::get(uri)
+            .body(Body::empty())
+            .expect("Failed building request")
+    }
+
+    #[tokio::test]
+    async fn reports_health_before_models_load() {
+        let state = state(1);
+        assert_eq!(call(&state, get("/healthz")).await.0, StatusCode::OK);
+
+        let (status, body) = call(&state, get("/readyz")).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.contains("Models are still loading"));
+
+        assert_eq!(
+            call(&state, get("/embeddings")).await,
+            (StatusCode::OK, "[]".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_jobs_while_queue_is_full() {
+        let state = state(1);
+        let detect = || {
+            Request::post("/detect")
+                .body(Body::from("frame"))
+                .expect("Failed building request")
+        };
+
+        let permit = state.queue.clone().try_acquire_owned().ok();
+        let (status, body) = call(&state, detect()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.contains("Job queue is full"));
+
+        drop(permit);
+        let (status, body) = call(&state, detect()).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.contains("Models are still loading"));
+        assert_eq!(state.queue.available_permits(), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_swap_requests() {
+        let state = state(1);
+
+        let not_multipart = Request::post("/swap")
+            .header(header::CONTENT_TYPE, "text/plain")
+            .body(Body::from("target"))
+            .expect("Failed building request");
+        assert_eq!(call(&state, not_multipart).await.0, StatusCode::BAD_REQUEST);
+
+        let truncated = Request::post("/swap")
+            .header(
+                header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={}", BOUNDARY),
+            )
+            .body(Body::from(format!("--{}\r\nContent-Disp", BOUNDARY)))
+            .expect("Failed building request");
+        assert_eq!(call(&state, truncated).await.0, StatusCode::BAD_REQUEST);
+
+        let (status, body) = call(&state, multipart(&[("source", b"face")])).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.contains("Missing multipart field `target`"));
+
+        let non_utf8 = multipart(&[("target", b"frame"), ("embedding", &[0xFF, 0xFE])]);
+        assert_eq!(call(&state, non_utf8).await.0, StatusCode::BAD_REQUEST);
+
+        for id in [&b"../secret"[..], b"unknown"] {
+            let (status, body) = call(
+                &state,
+                multipart(&[("target", b"frame"), ("embedding", id)]),
+            )
+            .await;
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert!(body.contains("embedding id"));
+        }
+    }
+}
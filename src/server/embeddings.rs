@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use crate::{
+    model::{embedding::EMBEDDING_EXTENSION, Embedding},
+    Error, Result,
+};
+
+/// Folder of `.emb` files addressed by file stem
+#[derive(Debug, Clone)]
+pub struct EmbeddingStore {
+    dir: PathBuf,
+}
+
+impl EmbeddingStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn list(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(vec![]);
+        }
+
+        let mut ids = vec![];
+        for entry in std::fs::read_dir(&self.dir).map_err(Error::as_unknown_error)? {
+            let path = entry.map_err(Error::as_unknown_error)?.path();
+            if !Embedding::is_embedding_file(&path) {
+                continue;
+            }
+            if let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                ids.push(id.to_owned());
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    pub fn load(&self, id: &str) -> Result<Embedding> {
+        let path = self.path_of(id)?;
+        if !path.exists() {
+            return Err(Error::InvalidModelIOError(format!(
+                "Unknown embedding id: {}",
+                id
+            )));
+        }
+        Embedding::load(&path)
+    }
+
+    /// Ids are plain file stems so requests can't escape the store folder
+    fn path_of(&self, id: &str) -> Result<PathBuf> {
+        if !is_valid_id(id) {
+            return Err(Error::InvalidModelIOError(format!(
+                "Invalid embedding id: {}",
+                id
+            )));
+        }
+        Ok(self.dir.join(format!("{}.{}", id, EMBEDDING_EXTENSION)))
+    }
+}
+
+fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_valid_id;
+
+    #[test]
+    fn rejects_path_like_ids() {
+        assert!(is_valid_id("alice_01"));
+        assert!(is_valid_id("bob-2"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("../secret"));
+        assert!(!is_valid_id("a/b"));
+        assert!(!is_valid_id("face.emb"));
+    }
+}
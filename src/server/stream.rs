@@ -0,0 +1,149 @@
+//! Live frame websocket
+//!
+//! - binary message: encoded frame (jpeg), answered with the swapped jpeg
+//! - text message: `{"embedding": "<id>"}` sets this connection source,
+//!   `{"embedding": null}` goes back to the server default
+//!
+//! Only the latest received frame waits for processing, older ones are
+//! dropped so a fast client doesn't build up latency. Replies keep the
+//! order frames were received in. Errors are sent as `{"error": "..."}`.
+
+use std::sync::Mutex;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        State, WebSocketUpgrade,
+    },
+    response::Response,
+};
+use futures_util::{SinkExt, StreamExt};
+use tokio::sync::watch;
+
+use crate::{image::Image, swapper::Identity, Error};
+
+use super::{load_embedding, spawn_job, ApiError, AppState, ErrorBody};
+
+#[derive(serde::Deserialize)]
+struct Control {
+    embedding: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct ControlAck {
+    embedding: Option<String>,
+}
+
+pub(crate) async fn stream(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.max_message_size(state.body_limit)
+        .on_upgrade(move |socket| handle_socket(socket, state))
+}
+
+async fn handle_socket(socket: WebSocket, state: AppState) {
+    let (sender, mut receiver) = socket.split();
+    let sender = tokio::sync::Mutex::new(sender);
+    let (frame_tx, mut frame_rx) = watch::channel::<Option<Vec<u8>>>(None);
+    let identity = Mutex::new(None::<Identity>);
+
+    let reader = async {
+        while let Some(Ok(message)) = receiver.next().await {
+            match message {
+                Message::Binary(frame) => {
+                    // Replaces a frame that is still waiting
+                    let _ = frame_tx.send(Some(frame));
+                }
+                Message::Text(text) => {
+                    let reply = match set_source(&state, &identity, &text).await {
+                        Ok(ack) => serde_json::to_string(&ack),
+                        Err(err) => serde_json::to_string(&ErrorBody { error: err.1 }),
+                    };
+                    let Ok(reply) = reply else { continue };
+                    if sender
+                        .lock()
+                        .await
+                        .send(Message::Text(reply))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+    };
+
+    let writer = async {
+        while frame_rx.changed().await.is_ok() {
+            let Some(frame) = frame_rx.borrow_and_update().clone() else {
+                continue;
+            };
+            let identity = identity.lock().ok().and_then(|identity| identity.clone());
+
+            let reply = match process_frame(&state, frame, identity).await {
+                Ok(jpeg) => Message::Binary(jpeg),
+                Err(err) => match serde_json::to_string(&ErrorBody { error: err.1 }) {
+                    Ok(text) => Message::Text(text),
+                    Err(_) => continue,
+                },
+            };
+            if sender.lock().await.send(reply).await.is_err() {
+                break;
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = reader => {}
+        _ = writer => {}
+    }
+}
+
+async fn set_source(
+    state: &AppState,
+    identity: &Mutex<Option<Identity>>,
+    text: &str,
+) -> std::result::Result<ControlAck, ApiError> {
+    let control = serde_json::from_str::<Control>(text).map_err(ApiError::bad_request)?;
+
+    let loaded = match &control.embedding {
+        Some(id) => {
+            let embedding = load_embedding(state, id.clone()).await?;
+            let permit = state.wait_slot().await?;
+            Some(
+                spawn_job(state, permit, move |swapper| {
+                    swapper.identity_from_embedding(&embedding)
+                })
+                .await?,
+            )
+        }
+        None => None,
+    };
+
+    *identity
+        .lock()
+        .map_err(|err| ApiError::from(Error::as_guard_error(err)))? = loaded;
+    Ok(ControlAck {
+        embedding: control.embedding,
+    })
+}
+
+async fn process_frame(
+    state: &AppState,
+    frame: Vec<u8>,
+    identity: Option<Identity>,
+) -> std::result::Result<Vec<u8>, ApiError> {
+    state.swapper()?;
+    let permit = state.wait_slot().await?;
+
+    spawn_job(state, permit, move |swapper| {
+        let target = Image::from_bytes(&frame)?;
+        let output = match &identity {
+            Some(identity) => swapper.swap_with(&target, identity)?,
+            None => swapper.swap(&target)?,
+        };
+        output.encode(image::ImageFormat::Jpeg)
+    })
+    .await
+}
@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-pub use self::config::{Config, GuiConfig, ModelConfig};
+pub use self::config::{
+    Config, GuiConfig, HotkeyConfig, ImageOutputFormat, ModelConfig, OutputConfig,
+    OutputWindowConfig,
+};
 
 use crate::{gui::GuiSetting, result::Result, sync::debounce::Debounce};
 
@@ -36,7 +39,7 @@ impl GuiSetting for Setting {
                 return;
             };
             let (w, h) = (rect.max.x - rect.min.x, rect.max.y - rect.min.y);
-            let GuiConfig { width, height } = self.config.gui;
+            let GuiConfig { width, height, .. } = self.config.gui;
             if width != w || height != h {
                 self.config.gui.width = w;
                 self.config.gui.height = h;
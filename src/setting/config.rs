@@ -12,29 +12,302 @@ pub struct Config {
     pub gui: GuiConfig,
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
 pub struct ModelConfig {
+    /// Cuda execution provider, cpu otherwise
     pub cuda: bool,
+    /// Folder containing onnx models, `./models` when not set
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    /// Model file overrides, `dir` + default file name when not set
+    #[serde(default)]
+    pub detector: Option<PathBuf>,
+    #[serde(default)]
+    pub swapper: Option<PathBuf>,
+    #[serde(default)]
+    pub recognizer: Option<PathBuf>,
+    /// Min detection score
+    #[serde(default = "default_score_threshold")]
+    pub score_threshold: f32,
+    /// Max overlap (iou) before a detection is suppressed
+    #[serde(default = "default_nms_threshold")]
+    pub nms_threshold: f32,
+    /// Onnx runtime intra op threads
+    #[serde(default = "default_threads")]
+    pub threads: usize,
+    /// Swapped face edge fade in px, 0 pastes with hard edges
+    #[serde(default)]
+    pub blend_feather: usize,
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Clone, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
 pub struct GuiConfig {
     pub width: f32,
     pub height: f32,
+    /// Camera device index
+    #[serde(default)]
+    pub camera: i32,
+    /// Preview and camera recording frame rate target
+    #[serde(default = "default_fps")]
+    pub fps: u32,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub hotkeys: HotkeyConfig,
+    #[serde(default)]
+    pub output_window: OutputWindowConfig,
+}
+
+/// Detached output window for screen capture
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct OutputWindowConfig {
+    /// Fill around the frame with `key_color` instead of black
+    pub chroma_key: bool,
+    /// Rgb
+    pub key_color: [u8; 3],
+}
+
+impl OutputWindowConfig {
+    pub fn background(&self) -> [u8; 3] {
+        if self.chroma_key {
+            self.key_color
+        } else {
+            [0, 0, 0]
+        }
+    }
+}
+
+/// Shortcut per gui command like `Ctrl+Shift+S` or `F5`, empty to unbind
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct HotkeyConfig {
+    pub preview: String,
+    pub run: String,
+    pub snapshot: String,
+    pub record: String,
+    pub overlay: String,
+    pub perf_hud: String,
+    pub output_window: String,
+    pub next_source: String,
+    pub previous_source: String,
+    pub settings: String,
+    pub palette: String,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Debug, Default, PartialEq)]
+pub struct OutputConfig {
+    /// Folder the run output dialog opens in
+    #[serde(default)]
+    pub dir: Option<PathBuf>,
+    /// Format for still image outputs
+    #[serde(default)]
+    pub image_format: ImageOutputFormat,
+    /// Preview snapshots and recordings, `data/captures` when not set
+    #[serde(default)]
+    pub captures: Option<PathBuf>,
+}
+
+impl OutputConfig {
+    pub fn captures_dir(&self) -> Result<PathBuf> {
+        match &self.captures {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(std::env::current_dir()
+                .map_err(Error::as_unknown_error)?
+                .join("data")
+                .join("captures")),
+        }
+    }
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageOutputFormat {
+    #[default]
+    Png,
+    Jpeg,
+}
+
+impl ImageOutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageOutputFormat::Png => "png",
+            ImageOutputFormat::Jpeg => "jpg",
+        }
+    }
+}
+
+fn default_score_threshold() -> f32 {
+    0.5
+}
+
+fn default_nms_threshold() -> f32 {
+    0.4
+}
+
+fn default_threads() -> usize {
+    4
+}
+
+fn default_fps() -> u32 {
+    30
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            model: ModelConfig { cuda: false },
+            model: ModelConfig::default(),
             gui: GuiConfig {
                 width: 350.,
                 height: 450.,
+                camera: 0,
+                fps: default_fps(),
+                output: OutputConfig::default(),
+                hotkeys: HotkeyConfig::default(),
+                output_window: OutputWindowConfig::default(),
             },
         }
     }
 }
 
+impl Default for OutputWindowConfig {
+    fn default() -> Self {
+        Self {
+            chroma_key: false,
+            key_color: [0, 177, 64],
+        }
+    }
+}
+
+impl Default for HotkeyConfig {
+    fn default() -> Self {
+        Self {
+            preview: "F5".into(),
+            run: "F6".into(),
+            snapshot: "Ctrl+S".into(),
+            record: "Ctrl+R".into(),
+            overlay: "F3".into(),
+            perf_hud: "F2".into(),
+            output_window: "F4".into(),
+            next_source: "Ctrl+Right".into(),
+            previous_source: "Ctrl+Left".into(),
+            settings: "Ctrl+Comma".into(),
+            palette: "Ctrl+P".into(),
+        }
+    }
+}
+
+impl Default for ModelConfig {
+    fn default() -> Self {
+        Self {
+            cuda: false,
+            dir: None,
+            detector: None,
+            swapper: None,
+            recognizer: None,
+            score_threshold: default_score_threshold(),
+            nms_threshold: default_nms_threshold(),
+            threads: default_threads(),
+            blend_feather: 0,
+        }
+    }
+}
+
+impl ModelConfig {
+    pub fn model_dir(&self) -> Result<PathBuf> {
+        match &self.dir {
+            Some(dir) => Ok(dir.clone()),
+            None => Ok(std::env::current_dir()
+                .map_err(Error::as_unknown_error)?
+                .join("models")),
+        }
+    }
+
+    pub fn detector_path(&self) -> Result<PathBuf> {
+        self.model_path(&self.detector, "det_10g.onnx")
+    }
+
+    pub fn swapper_path(&self) -> Result<PathBuf> {
+        self.model_path(&self.swapper, "inswapper_128.onnx")
+    }
+
+    pub fn recognizer_path(&self) -> Result<PathBuf> {
+        self.model_path(
+            &self.recognizer,
+            &format!("{}.onnx", crate::model::VECTORIZATION_MODEL_NAME),
+        )
+    }
+
+    fn model_path(&self, file: &Option<PathBuf>, default_name: &str) -> Result<PathBuf> {
+        match file {
+            Some(file) => Ok(file.clone()),
+            None => Ok(self.model_dir()?.join(default_name)),
+        }
+    }
+
+    /// Whether going from `self` to `other` needs new onnx sessions
+    pub fn needs_reload(&self, other: &ModelConfig) -> bool {
+        self.cuda != other.cuda
+            || self.dir != other.dir
+            || self.detector != other.detector
+            || self.swapper != other.swapper
+            || self.recognizer != other.recognizer
+            || self.threads != other.threads
+    }
+
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+        if !(0. ..=1.).contains(&self.score_threshold) || self.score_threshold == 0. {
+            errors.push("Score threshold must be in (0, 1]".to_string());
+        }
+        if !(0. ..=1.).contains(&self.nms_threshold) || self.nms_threshold == 0. {
+            errors.push("NMS threshold must be in (0, 1]".to_string());
+        }
+        if !(1..=64).contains(&self.threads) {
+            errors.push("Threads must be between 1 and 64".to_string());
+        }
+        if self.blend_feather > 64 {
+            errors.push("Blend feather must be at most 64 px".to_string());
+        }
+
+        for (name, path) in [
+            ("Detector", self.detector_path()),
+            ("Swapper", self.swapper_path()),
+            ("Recognizer", self.recognizer_path()),
+        ] {
+            match path {
+                Ok(path) if path.is_file() => {}
+                Ok(path) => errors.push(format!("{} model not found: {}", name, path.display())),
+                Err(err) => errors.push(format!("{} model path: {}", name, err)),
+            }
+        }
+        errors
+    }
+}
+
+impl GuiConfig {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = vec![];
+        if self.camera < 0 {
+            errors.push("Camera device index can't be negative".to_string());
+        }
+        if !(1..=120).contains(&self.fps) {
+            errors.push("FPS must be between 1 and 120".to_string());
+        }
+        if let Some(dir) = &self.output.dir {
+            if !dir.is_dir() {
+                errors.push(format!("Output folder not found: {}", dir.display()));
+            }
+        }
+        if let Some(dir) = &self.output.captures {
+            if !dir.is_dir() {
+                errors.push(format!("Captures folder not found: {}", dir.display()));
+            }
+        }
+        errors
+    }
+}
+
 impl Config {
     pub fn get() -> Result<Config> {
         let config_dir = Self::get_config_dir()
@@ -70,6 +343,13 @@ impl Config {
         Ok(())
     }
 
+    /// Problems preventing the config from being applied, empty when valid
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = self.model.validate();
+        errors.extend(self.gui.validate());
+        errors
+    }
+
     fn get_config_dir() -> Result<PathBuf> {
         Ok(std::env::current_dir()
             .map_err(Error::as_unknown_error)?
@@ -93,3 +373,52 @@ impl Config {
             .map_err(|err| Error::UnknownError(Box::new(err)))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Config, ModelConfig};
+
+    #[test]
+    fn reads_config_without_new_fields() {
+        let config = serde_json::from_str::<Config>(
+            r#"{"model":{"cuda":false},"gui":{"width":350.0,"height":450.0}}"#,
+        )
+        .expect("Failed to parse config");
+
+        assert_eq!(config.model, ModelConfig::default());
+        assert_eq!(config.gui, Config::default().gui);
+    }
+
+    #[test]
+    fn validates_ranges_and_model_files() {
+        let mut config = Config::default();
+        config.model.dir = Some("does/not/exist".into());
+        config.model.score_threshold = 1.5;
+        config.gui.fps = 0;
+
+        let errors = config.validate();
+        assert!(errors.iter().any(|err| err.starts_with("Score threshold")));
+        assert!(errors.iter().any(|err| err.starts_with("FPS")));
+        assert_eq!(
+            errors
+                .iter()
+                .filter(|err| err.contains("model not found"))
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn reloads_only_for_session_changes() {
+        let config = ModelConfig::default();
+
+        let mut thresholds = config.clone();
+        thresholds.score_threshold = 0.7;
+        thresholds.blend_feather = 8;
+        assert!(!config.needs_reload(&thresholds));
+
+        let mut provider = config.clone();
+        provider.cuda = true;
+        assert!(config.needs_reload(&provider));
+    }
+}
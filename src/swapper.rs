@@ -0,0 +1,198 @@
+//! High level api for using noface as a library
+//!
+//! ```no_run
+//! use noface::{image::Image, model::FaceSelection, Swapper};
+//!
+//! let swapper = Swapper::builder().models("models").cuda(false).build()?;
+//! swapper.set_source(&Image::from_path("face.jpg".into(), None)?)?;
+//!
+//! let output = swapper.swap(&Image::from_path("photo.jpg".into(), None)?)?;
+//! output.save("out.png").map_err(noface::Error::ImageError)?;
+//!
+//! // group photo as source, average the first and third face
+//! let group = Image::from_path("group.jpg".into(), None)?;
+//! let identity = swapper.identity_from_image_with(&group, &FaceSelection::Average(vec![0, 2]))?;
+//! swapper.set_identity(identity)?;
+//! # Ok::<(), noface::Error>(())
+//! ```
+
+use std::{
+    path::PathBuf,
+    sync::{Mutex, RwLock},
+};
+
+use crate::{
+    image::Image,
+    model::{
+        data::{Face, VectorizedTensor},
+        register_ort, Embedding, FaceSelection, Model, SourceFace, VECTORIZATION_DIM,
+    },
+    setting::ModelConfig,
+    Error, Result,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct SwapperBuilder {
+    config: ModelConfig,
+}
+
+impl SwapperBuilder {
+    /// Folder containing det_10g.onnx, inswapper_128.onnx and w600k_r50.onnx
+    pub fn models(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.dir = Some(dir.into());
+        self
+    }
+
+    pub fn cuda(mut self, cuda: bool) -> Self {
+        self.config.cuda = cuda;
+        self
+    }
+
+    pub fn config(mut self, config: ModelConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Registers onnx runtime environment (once per process) and loads models
+    pub fn build(self) -> Result<Swapper> {
+        register_ort(&self.config)?;
+        Ok(Swapper {
+            model: Mutex::new(Model::new(&self.config)?),
+            identity: RwLock::new(None),
+        })
+    }
+}
+
+/// Source face prepared for swapping
+#[derive(Debug, Clone)]
+pub struct Identity(VectorizedTensor);
+
+impl Identity {
+    pub fn vector(&self) -> &VectorizedTensor {
+        &self.0
+    }
+}
+
+/// Thread safe face swapper, calls are serialized on the underlying models
+pub struct Swapper {
+    model: Mutex<Model>,
+    identity: RwLock<Option<Identity>>,
+}
+
+impl Swapper {
+    pub fn builder() -> SwapperBuilder {
+        SwapperBuilder::default()
+    }
+
+    /// From the best scoring face of `image`
+    pub fn identity_from_image(&self, image: &Image) -> Result<Identity> {
+        self.identity_from_image_with(image, &FaceSelection::default())
+    }
+
+    pub fn identity_from_image_with(
+        &self,
+        image: &Image,
+        selection: &FaceSelection,
+    ) -> Result<Identity> {
+        let (_, vec_tensor) = self
+            .model
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .vectorize_tensor_with(image.clone().into(), selection)?;
+        Ok(Identity(vec_tensor))
+    }
+
+    /// Every face of `image` with aligned crop and raw vector, pass the chosen
+    /// vectors to [`Swapper::identity_from_vector`]
+    pub fn source_faces(&self, image: &Image) -> Result<Vec<SourceFace>> {
+        self.model
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .embed_faces(image.clone().into())
+    }
+
+    pub fn identity_from_embedding(&self, embedding: &Embedding) -> Result<Identity> {
+        Ok(Identity(
+            self.model
+                .lock()
+                .map_err(Error::as_guard_error)?
+                .source_from_embedding(embedding)?,
+        ))
+    }
+
+    /// From a raw recognition vector (ex: output of [`Swapper::embed`])
+    pub fn identity_from_vector(&self, raw: &VectorizedTensor) -> Result<Identity> {
+        if raw.len() != VECTORIZATION_DIM {
+            return Err(Error::InvalidModelIOError(format!(
+                "Expected {}d recognition vector, got {}d",
+                VECTORIZATION_DIM,
+                raw.len()
+            )));
+        }
+        Ok(Identity(
+            self.model
+                .lock()
+                .map_err(Error::as_guard_error)?
+                .prep_embedding(raw),
+        ))
+    }
+
+    /// Raw recognition vector of the best scoring face
+    pub fn embed(&self, image: &Image) -> Result<VectorizedTensor> {
+        self.embed_with(image, &FaceSelection::default())
+    }
+
+    pub fn embed_with(&self, image: &Image, selection: &FaceSelection) -> Result<VectorizedTensor> {
+        let (_, raw) = self
+            .model
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .embed_tensor_with(image.clone().into(), selection)?;
+        Ok(raw)
+    }
+
+    pub fn set_source(&self, image: &Image) -> Result<()> {
+        let identity = self.identity_from_image(image)?;
+        self.set_identity(identity)
+    }
+
+    pub fn set_identity(&self, identity: Identity) -> Result<()> {
+        *self.identity.write().map_err(Error::as_guard_error)? = Some(identity);
+        Ok(())
+    }
+
+    pub fn identity(&self) -> Result<Option<Identity>> {
+        Ok(self.identity.read().map_err(Error::as_guard_error)?.clone())
+    }
+
+    /// Swaps current source into image, errors when no source is set
+    pub fn swap(&self, image: &Image) -> Result<Image> {
+        let Some(identity) = self.identity()? else {
+            return Err(Error::InvalidModelIOError(
+                "Swapper source is not set".into(),
+            ));
+        };
+        self.swap_with(image, &identity)
+    }
+
+    pub fn swap_with(&self, image: &Image, identity: &Identity) -> Result<Image> {
+        Ok(self
+            .model
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .run(image.clone().into(), identity.0.clone())?
+            .into())
+    }
+
+    pub fn detect(&self, image: &Image) -> Result<Vec<Face>> {
+        self.model
+            .lock()
+            .map_err(Error::as_guard_error)?
+            .detect(image.clone().into())
+    }
+}
+
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Swapper>();
+};